@@ -26,4 +26,24 @@ mod benches {
             )))
         });
     }
+
+    #[bench]
+    fn bench_euclidean_distance_transform(b: &mut Bencher) {
+        let mut terrain = LocalRoomTerrain::new_from_bits(Box::new([0; 2500]));
+        b.iter(|| {
+            black_box(euclidean_distance_transform_from_terrain(&*black_box(
+                &mut terrain,
+            )))
+        });
+    }
+
+    #[bench]
+    fn bench_octile_distance_transform(b: &mut Bencher) {
+        let mut terrain = LocalRoomTerrain::new_from_bits(Box::new([0; 2500]));
+        b.iter(|| {
+            black_box(octile_distance_transform_from_terrain(&*black_box(
+                &mut terrain,
+            )))
+        });
+    }
 }
@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+
+use screeps::{
+    constants::Direction,
+    local::{LocalCostMatrix, RoomXY},
+};
+
+use crate::large_cost_matrix::LargeCostMatrix;
+
+/// Summary information about a single connected region of a room.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    /// The number of positions in the region
+    pub size: u16,
+    /// The top-left corner of the region's bounding box
+    pub top_left: RoomXY,
+    /// The bottom-right corner of the region's bounding box
+    pub bottom_right: RoomXY,
+}
+
+/// The connected regions of a room, as calculated by [`label_regions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionLabels {
+    /// The region ID of every position, which is its index into `regions`;
+    /// obstacle positions have the value u16::MAX.
+    pub labels: LargeCostMatrix,
+    /// Information about each region, indexed by region ID
+    pub regions: Vec<Region>,
+}
+
+impl RegionLabels {
+    /// Gets the region ID of the given position, or `None` if it's an
+    /// obstacle.
+    pub fn region_id(&self, xy: RoomXY) -> Option<u16> {
+        match self.labels.get(xy) {
+            u16::MAX => None,
+            id => Some(id),
+        }
+    }
+
+    /// Gets the region containing the given position, or `None` if it's an
+    /// obstacle.
+    pub fn region(&self, xy: RoomXY) -> Option<&Region> {
+        self.region_id(xy).map(|id| &self.regions[usize::from(id)])
+    }
+
+    /// Whether the two positions are in the same region, meaning a creep can
+    /// move from one to the other.
+    pub fn connected(&self, a: RoomXY, b: RoomXY) -> bool {
+        match (self.region_id(a), self.region_id(b)) {
+            (Some(a_id), Some(b_id)) => a_id == b_id,
+            _ => false,
+        }
+    }
+}
+
+/// Labels each position of a room with the ID of the connected region it
+/// belongs to, where regions are connected in all 8 directions in the same way
+/// as creep movement.
+///
+/// The obstacles Cost Matrix should have u8::MAX set on all positions that are
+/// obstacles, and 0 everywhere else. Region IDs are assigned in the order the
+/// regions are first found when scanning the room.
+pub fn label_regions(obstacles: &LocalCostMatrix) -> RegionLabels {
+    let mut labels = LargeCostMatrix::new_with_default(u16::MAX);
+    let mut regions: Vec<Region> = Vec::new();
+    let mut queue: VecDeque<RoomXY> = VecDeque::new();
+
+    for (origin, val) in obstacles.iter() {
+        if val == u8::MAX || labels.get(origin) != u16::MAX {
+            continue;
+        }
+
+        let id = regions.len() as u16;
+        let mut region = Region {
+            size: 0,
+            top_left: origin,
+            bottom_right: origin,
+        };
+
+        labels.set(origin, id);
+        queue.push_back(origin);
+        while let Some(current_position) = queue.pop_front() {
+            region.size += 1;
+            region.top_left.x = region.top_left.x.min(current_position.x);
+            region.top_left.y = region.top_left.y.min(current_position.y);
+            region.bottom_right.x = region.bottom_right.x.max(current_position.x);
+            region.bottom_right.y = region.bottom_right.y.max(current_position.y);
+
+            Direction::iter()
+                .filter_map(|dir| current_position.checked_add_direction(*dir))
+                .filter(|position| obstacles.get(*position) != u8::MAX)
+                .for_each(|position| {
+                    if labels.get(position) == u16::MAX {
+                        labels.set(position, id);
+                        queue.push_back(position);
+                    }
+                });
+        }
+
+        regions.push(region);
+    }
+
+    RegionLabels { labels, regions }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    #[test]
+    fn enclosed_pocket() {
+        let mut obstacles = LocalCostMatrix::new();
+        // a ring of walls around a 3x3 pocket from (11, 11) to (13, 13)
+        for i in 10..=14 {
+            for xy in [
+                make_xy(i, 10),
+                make_xy(i, 14),
+                make_xy(10, i),
+                make_xy(14, i),
+            ] {
+                obstacles.set(xy, u8::MAX);
+            }
+        }
+
+        let labels = label_regions(&obstacles);
+        assert_eq!(labels.regions.len(), 2);
+        assert_eq!(labels.regions[0].size, 2500 - 25);
+        assert_eq!(
+            labels.region(make_xy(12, 12)),
+            Some(&Region {
+                size: 9,
+                top_left: make_xy(11, 11),
+                bottom_right: make_xy(13, 13),
+            })
+        );
+        assert_eq!(labels.region_id(make_xy(10, 10)), None);
+        assert!(labels.connected(make_xy(0, 0), make_xy(49, 49)));
+        assert!(!labels.connected(make_xy(0, 0), make_xy(12, 12)));
+    }
+
+    #[test]
+    fn diagonal_gaps_connect() {
+        let mut obstacles = LocalCostMatrix::new();
+        // a diagonal line of walls can be crossed diagonally
+        for i in 0..50 {
+            obstacles.set(make_xy(i, i), u8::MAX);
+        }
+        assert_eq!(label_regions(&obstacles).regions.len(), 1);
+
+        // but a staircase of walls can't
+        for i in 0..49 {
+            obstacles.set(make_xy(i + 1, i), u8::MAX);
+        }
+        let labels = label_regions(&obstacles);
+        assert_eq!(labels.regions.len(), 2);
+        assert_eq!(labels.regions[0].size + labels.regions[1].size, 2500 - 99);
+    }
+}
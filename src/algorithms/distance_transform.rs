@@ -5,7 +5,19 @@ use screeps::{
     local::{LocalCostMatrix, LocalRoomTerrain, RoomCoordinate, RoomXY},
 };
 
-use crate::room_coordinate::{range_exclusive, range_inclusive};
+use crate::{
+    large_cost_matrix::LargeCostMatrix,
+    offline_map::{OfflineObject, OfflineRoomData},
+    room_coordinate::{range_exclusive, range_inclusive},
+};
+
+/// The cost of an orthogonal step in the values produced by the octile
+/// distance transforms.
+pub const OCTILE_ORTHOGONAL_COST: u16 = 10;
+
+/// The cost of a diagonal step in the values produced by the octile distance
+/// transforms, approximating `OCTILE_ORTHOGONAL_COST * sqrt(2)`.
+pub const OCTILE_DIAGONAL_COST: u16 = 14;
 
 /// Provides a Cost Matrix with values equal to the Chebyshev distance from any
 /// wall terrain. This does *not* calculate based on constructed walls, only
@@ -389,3 +401,385 @@ pub fn manhattan_distance_transform_from_cost_matrix(mut cm: LocalCostMatrix) ->
 
     cm
 }
+
+/// Provides a Cost Matrix with values equal to the squared Euclidean distance
+/// from any wall terrain. This does *not* calculate based on constructed walls,
+/// only terrain walls.
+pub fn euclidean_distance_transform_from_terrain(
+    room_terrain: &LocalRoomTerrain,
+) -> LargeCostMatrix {
+    let mut initial_cm = LocalCostMatrix::new();
+
+    for (xy, cm_val) in initial_cm.iter_mut() {
+        *cm_val = match room_terrain.get_xy(xy) {
+            screeps::constants::Terrain::Wall => 0,
+            _ => u8::MAX,
+        };
+    }
+    euclidean_distance_transform_from_cost_matrix(initial_cm)
+}
+
+/// Provides a Cost Matrix with values equal to the squared Euclidean distance
+/// from any position in the provided initial Cost Matrix with a value set to 0.
+///
+/// Squared distances are used so that the values are exact integers; take the
+/// square root to get the true distance. Positions which have no 0 position to
+/// measure from will have the value u16::MAX.
+pub fn euclidean_distance_transform_from_cost_matrix(cm: LocalCostMatrix) -> LargeCostMatrix {
+    // Exact transform using the lower envelope of parabolas, one dimension at a
+    // time: see "Distance Transforms of Sampled Functions", Felzenszwalb and
+    // Huttenlocher
+    const UNSEEDED: f64 = 1e9;
+    let size = ROOM_SIZE as usize;
+
+    let mut column_distances = [[UNSEEDED; ROOM_SIZE as usize]; ROOM_SIZE as usize];
+    for (x, column_output) in column_distances.iter_mut().enumerate() {
+        let column: Vec<f64> = (0..size)
+            .map(|y| match cm.get(room_xy(x, y)) {
+                0 => 0.,
+                _ => UNSEEDED,
+            })
+            .collect();
+        squared_distance_transform_1d(&column, column_output);
+    }
+
+    let mut output_cm = LargeCostMatrix::new();
+    let mut row_distances = [0.; ROOM_SIZE as usize];
+    for y in 0..size {
+        let row: Vec<f64> = column_distances.iter().map(|column| column[y]).collect();
+        squared_distance_transform_1d(&row, &mut row_distances);
+        for (x, distance) in row_distances.iter().enumerate() {
+            let value = if *distance >= u16::MAX as f64 {
+                u16::MAX
+            } else {
+                *distance as u16
+            };
+            output_cm.set(room_xy(x, y), value);
+        }
+    }
+
+    output_cm
+}
+
+// Calculates the squared distance transform of a sampled function in one
+// dimension.
+fn squared_distance_transform_1d(f: &[f64], output: &mut [f64]) {
+    let n = f.len();
+    // Positions of the parabolas forming the lower envelope, and the
+    // boundaries between them
+    let mut parabolas = vec![0; n];
+    let mut boundaries = vec![0.; n + 1];
+    let mut k = 0;
+    boundaries[0] = f64::NEG_INFINITY;
+    boundaries[1] = f64::INFINITY;
+
+    let intersection = |q: usize, p: usize| {
+        ((f[q] + (q * q) as f64) - (f[p] + (p * p) as f64)) / (2 * q - 2 * p) as f64
+    };
+
+    for q in 1..n {
+        let mut s = intersection(q, parabolas[k]);
+        while s <= boundaries[k] {
+            k -= 1;
+            s = intersection(q, parabolas[k]);
+        }
+        k += 1;
+        parabolas[k] = q;
+        boundaries[k] = s;
+        boundaries[k + 1] = f64::INFINITY;
+    }
+
+    k = 0;
+    for (q, out) in output.iter_mut().enumerate().take(n) {
+        while boundaries[k + 1] < q as f64 {
+            k += 1;
+        }
+        let offset = q.abs_diff(parabolas[k]);
+        *out = (offset * offset) as f64 + f[parabolas[k]];
+    }
+}
+
+/// Provides a Cost Matrix with values equal to the octile distance from any
+/// wall terrain. This does *not* calculate based on constructed walls, only
+/// terrain walls.
+pub fn octile_distance_transform_from_terrain(room_terrain: &LocalRoomTerrain) -> LargeCostMatrix {
+    let mut initial_cm = LocalCostMatrix::new();
+
+    for (xy, cm_val) in initial_cm.iter_mut() {
+        *cm_val = match room_terrain.get_xy(xy) {
+            screeps::constants::Terrain::Wall => 0,
+            _ => u8::MAX,
+        };
+    }
+    octile_distance_transform_from_cost_matrix(initial_cm)
+}
+
+/// Provides a Cost Matrix with values equal to the octile distance from any
+/// position in the provided initial Cost Matrix with a value set to 0.
+///
+/// Orthogonal steps cost [`OCTILE_ORTHOGONAL_COST`] and diagonal steps cost
+/// [`OCTILE_DIAGONAL_COST`], so the values are scaled up from the distance in
+/// positions. Positions which have no 0 position to measure from will have the
+/// value u16::MAX.
+pub fn octile_distance_transform_from_cost_matrix(cm: LocalCostMatrix) -> LargeCostMatrix {
+    let size = ROOM_SIZE as usize;
+    let mut output_cm = LargeCostMatrix::new_with_default(u16::MAX);
+    for (xy, val) in cm.iter() {
+        if val == 0 {
+            output_cm.set(xy, 0);
+        }
+    }
+
+    let relax =
+        |output_cm: &mut LargeCostMatrix, x: usize, y: usize, offsets: &[(isize, isize)]| {
+            let current = room_xy(x, y);
+            let best = offsets
+                .iter()
+                .filter_map(|(dx, dy)| {
+                    let nx = x.checked_add_signed(*dx).filter(|nx| *nx < size)?;
+                    let ny = y.checked_add_signed(*dy).filter(|ny| *ny < size)?;
+                    let step = if *dx != 0 && *dy != 0 {
+                        OCTILE_DIAGONAL_COST
+                    } else {
+                        OCTILE_ORTHOGONAL_COST
+                    };
+                    Some(output_cm.get(room_xy(nx, ny)).saturating_add(step))
+                })
+                .min()
+                .unwrap_or(u16::MAX);
+            if best < output_cm.get(current) {
+                output_cm.set(current, best);
+            }
+        };
+
+    // Pass 1: Top-to-Bottom, Left-to-Right
+    for y in 0..size {
+        for x in 0..size {
+            relax(&mut output_cm, x, y, &[(-1, 0), (-1, -1), (0, -1), (1, -1)]);
+        }
+    }
+
+    // Pass 2: Bottom-to-Top, Right-to-Left
+    for y in (0..size).rev() {
+        for x in (0..size).rev() {
+            relax(&mut output_cm, x, y, &[(1, 0), (1, 1), (0, 1), (-1, 1)]);
+        }
+    }
+
+    output_cm
+}
+
+/// Creates a LocalCostMatrix suitable as the initial Cost Matrix for any of the
+/// `_from_cost_matrix` distance transforms, such that all the positions which
+/// are terrain walls or are occupied by one of the provided object positions
+/// will have the value 0, and all other positions will have a value of 255
+/// (u8::MAX).
+pub fn get_initial_cm_from_terrain_and_objects<I>(
+    room_terrain: &LocalRoomTerrain,
+    object_positions: I,
+) -> LocalCostMatrix
+where
+    I: IntoIterator<Item = RoomXY>,
+{
+    let mut initial_cm = LocalCostMatrix::new();
+
+    for (xy, cm_val) in initial_cm.iter_mut() {
+        *cm_val = match room_terrain.get_xy(xy) {
+            screeps::constants::Terrain::Wall => 0,
+            _ => u8::MAX,
+        };
+    }
+    for xy in object_positions {
+        initial_cm.set(xy, 0);
+    }
+
+    initial_cm
+}
+
+// Seeds the obstacle objects, along with keeper lairs, which can be walked on
+// but are always surrounded by source keepers; portals and extractors aren't
+// seeded
+fn initial_cm_from_offline_room(room: &OfflineRoomData) -> LocalCostMatrix {
+    get_initial_cm_from_terrain_and_objects(
+        &room.terrain,
+        room.objects
+            .iter()
+            .filter(|object| {
+                object.is_obstacle() || matches!(object, OfflineObject::KeeperLair { .. })
+            })
+            .filter_map(|object| object.xy()),
+    )
+}
+
+/// Provides a Cost Matrix with values equal to the Chebyshev distance from any
+/// wall terrain or object in a room from a map dump which blocks movement, such
+/// as constructed walls, the controller, sources, minerals and terminals, or
+/// from keeper lairs. Portals and extractors are ignored.
+pub fn chebyshev_distance_transform_from_offline_room(room: &OfflineRoomData) -> LocalCostMatrix {
+    chebyshev_distance_transform_from_cost_matrix(initial_cm_from_offline_room(room))
+}
+
+/// Provides a Cost Matrix with values equal to the Manhattan distance from any
+/// wall terrain or object in a room from a map dump which blocks movement, such
+/// as constructed walls, the controller, sources, minerals and terminals, or
+/// from keeper lairs. Portals and extractors are ignored.
+pub fn manhattan_distance_transform_from_offline_room(room: &OfflineRoomData) -> LocalCostMatrix {
+    manhattan_distance_transform_from_cost_matrix(initial_cm_from_offline_room(room))
+}
+
+/// Provides a Cost Matrix with values equal to the squared Euclidean distance
+/// from any wall terrain or object in a room from a map dump which blocks
+/// movement, such as constructed walls, the controller, sources, minerals and
+/// terminals, or from keeper lairs. Portals and extractors are ignored.
+pub fn euclidean_distance_transform_from_offline_room(room: &OfflineRoomData) -> LargeCostMatrix {
+    euclidean_distance_transform_from_cost_matrix(initial_cm_from_offline_room(room))
+}
+
+/// Provides a Cost Matrix with values equal to the octile distance from any
+/// wall terrain or object in a room from a map dump which blocks movement, such
+/// as constructed walls, the controller, sources, minerals and terminals, or
+/// from keeper lairs. Portals and extractors are ignored.
+pub fn octile_distance_transform_from_offline_room(room: &OfflineRoomData) -> LargeCostMatrix {
+    octile_distance_transform_from_cost_matrix(initial_cm_from_offline_room(room))
+}
+
+#[inline]
+fn room_xy(x: usize, y: usize) -> RoomXY {
+    // SAFETY: callers only pass coordinates within 0..ROOM_SIZE
+    unsafe { RoomXY::unchecked_new(x as u8, y as u8) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    fn seeds(positions: &[RoomXY]) -> LocalCostMatrix {
+        let mut cm = LocalCostMatrix::new();
+        for (_, cm_val) in cm.iter_mut() {
+            *cm_val = u8::MAX;
+        }
+        for xy in positions {
+            cm.set(*xy, 0);
+        }
+        cm
+    }
+
+    #[test]
+    fn euclidean_matches_brute_force() {
+        let positions = [make_xy(3, 4), make_xy(40, 10), make_xy(25, 49)];
+        let output = euclidean_distance_transform_from_cost_matrix(seeds(&positions));
+        for (xy, val) in output.iter() {
+            let expected = positions
+                .iter()
+                .map(|seed| {
+                    let dx = xy.x.u8().abs_diff(seed.x.u8()) as u16;
+                    let dy = xy.y.u8().abs_diff(seed.y.u8()) as u16;
+                    dx * dx + dy * dy
+                })
+                .min()
+                .unwrap();
+            assert_eq!(val, expected, "at {xy}");
+        }
+    }
+
+    #[test]
+    fn octile_matches_brute_force() {
+        let positions = [make_xy(3, 4), make_xy(40, 10), make_xy(25, 49)];
+        let output = octile_distance_transform_from_cost_matrix(seeds(&positions));
+        for (xy, val) in output.iter() {
+            let expected = positions
+                .iter()
+                .map(|seed| {
+                    let dx = xy.x.u8().abs_diff(seed.x.u8()) as u16;
+                    let dy = xy.y.u8().abs_diff(seed.y.u8()) as u16;
+                    OCTILE_DIAGONAL_COST * dx.min(dy)
+                        + OCTILE_ORTHOGONAL_COST * (dx.max(dy) - dx.min(dy))
+                })
+                .min()
+                .unwrap();
+            assert_eq!(val, expected, "at {xy}");
+        }
+    }
+
+    #[test]
+    fn unseeded_transforms() {
+        let cm = seeds(&[]);
+        assert!(euclidean_distance_transform_from_cost_matrix(cm.clone())
+            .iter()
+            .all(|(_, val)| val == u16::MAX));
+        assert!(octile_distance_transform_from_cost_matrix(cm)
+            .iter()
+            .all(|(_, val)| val == u16::MAX));
+    }
+
+    #[test]
+    fn euclidean_from_terrain() {
+        let mut bits = Box::new([0; 2500]);
+        bits[10 * 50 + 10] = 1;
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        let output = euclidean_distance_transform_from_terrain(&terrain);
+        assert_eq!(output.get(make_xy(10, 10)), 0);
+        assert_eq!(output.get(make_xy(13, 14)), 25);
+    }
+
+    #[test]
+    fn offline_room_objects_are_seeds() {
+        use screeps::{game::map::RoomStatus, local::RoomName};
+
+        let room_name = RoomName::new("W1N1").unwrap();
+        let room = OfflineRoomData {
+            room_name,
+            status: RoomStatus::Normal,
+            bus: false,
+            terrain: LocalRoomTerrain::new_from_bits(Box::new([0; 2500])),
+            objects: vec![
+                OfflineObject::ConstructedWall {
+                    id: "5bb64cc4f1ee994d0023982b".parse().unwrap(),
+                    room: room_name,
+                    x: RoomCoordinate::new(10).unwrap(),
+                    y: RoomCoordinate::new(10).unwrap(),
+                },
+                OfflineObject::KeeperLair {
+                    id: "5bb64cc4f1ee994d0023982c".parse().unwrap(),
+                    room: room_name,
+                    x: RoomCoordinate::new(30).unwrap(),
+                    y: RoomCoordinate::new(30).unwrap(),
+                },
+                // extractors can be walked on, so they aren't seeds
+                OfflineObject::Extractor {
+                    id: "5bb64cc4f1ee994d0023982d".parse().unwrap(),
+                    room: room_name,
+                    x: RoomCoordinate::new(20).unwrap(),
+                    y: RoomCoordinate::new(20).unwrap(),
+                },
+                OfflineObject::Unknown,
+            ],
+        };
+
+        let chebyshev = chebyshev_distance_transform_from_offline_room(&room);
+        assert_eq!(chebyshev.get(make_xy(10, 10)), 0);
+        assert_eq!(chebyshev.get(make_xy(12, 13)), 3);
+        assert_eq!(chebyshev.get(make_xy(30, 30)), 0);
+        assert_eq!(chebyshev.get(make_xy(31, 29)), 1);
+        assert_eq!(chebyshev.get(make_xy(20, 20)), 10);
+        // with no walls in the terrain, the edges are only as far as the
+        // nearest object
+        assert_eq!(chebyshev.get(make_xy(0, 0)), 10);
+
+        let manhattan = manhattan_distance_transform_from_offline_room(&room);
+        assert_eq!(manhattan.get(make_xy(12, 13)), 5);
+
+        let euclidean = euclidean_distance_transform_from_offline_room(&room);
+        assert_eq!(euclidean.get(make_xy(12, 13)), 13);
+
+        let octile = octile_distance_transform_from_offline_room(&room);
+        assert_eq!(
+            octile.get(make_xy(12, 13)),
+            2 * OCTILE_DIAGONAL_COST + OCTILE_ORTHOGONAL_COST
+        );
+    }
+}
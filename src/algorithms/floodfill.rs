@@ -1,8 +1,11 @@
-use std::collections::VecDeque;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
 
 use crate::large_cost_matrix::LargeCostMatrix;
 use screeps::{
-    constants::Direction,
+    constants::{Direction, Terrain},
     local::{LocalCostMatrix, LocalRoomTerrain, RoomXY},
 };
 
@@ -13,7 +16,7 @@ pub fn get_obstacles_lcm_from_terrain(room_terrain: &LocalRoomTerrain) -> LocalC
     let mut obstacles = LocalCostMatrix::new();
     for (xy, cm_val) in obstacles.iter_mut() {
         *cm_val = match room_terrain.get_xy(xy) {
-            screeps::constants::Terrain::Wall => u8::MAX,
+            Terrain::Wall => u8::MAX,
             _ => 0,
         };
     }
@@ -21,6 +24,26 @@ pub fn get_obstacles_lcm_from_terrain(room_terrain: &LocalRoomTerrain) -> LocalC
     obstacles
 }
 
+/// Creates a LargeCostMatrix of movement costs from LocalRoomTerrain, such that
+/// all the positions which are Walls will have the value u16::MAX, and all
+/// other positions will have the provided plain or swamp cost.
+pub fn get_move_costs_from_terrain(
+    room_terrain: &LocalRoomTerrain,
+    plain_cost: u16,
+    swamp_cost: u16,
+) -> LargeCostMatrix {
+    let mut costs = LargeCostMatrix::new();
+    for (xy, cm_val) in costs.iter_mut() {
+        *cm_val = match room_terrain.get_xy(xy) {
+            Terrain::Wall => u16::MAX,
+            Terrain::Swamp => swamp_cost,
+            Terrain::Plain => plain_cost,
+        };
+    }
+
+    costs
+}
+
 /// Generates a distance floodfill from origin positions.
 ///
 /// Takes a Vec of origin locations to start the floodfill from, and a Cost
@@ -131,3 +154,129 @@ pub fn reachability_floodfill(
 
     ret_cm
 }
+
+/// Generates a weighted distance floodfill from origin positions.
+///
+/// Takes a Vec of origin locations to start the floodfill from, and a Cost
+/// Matrix of movement costs, and produces a `LargeCostMatrix` with the lowest
+/// cumulative cost to reach each position from any of the origin points.
+/// Positions which can't be reached within `max_distance` will have the value
+/// u16::MAX.
+///
+/// The movement costs Cost Matrix holds the cost of stepping onto each
+/// position, with u16::MAX set on all positions that are obstacles. Callers
+/// with a `LocalCostMatrix` must convert it with `LargeCostMatrix::from`, which
+/// keeps u8::MAX positions as obstacles.
+pub fn weighted_floodfill(
+    origins: &Vec<RoomXY>,
+    costs: &LargeCostMatrix,
+    max_distance: u16,
+) -> LargeCostMatrix {
+    let mut output_cm = LargeCostMatrix::new_with_default(u16::MAX);
+
+    let mut heap: BinaryHeap<Reverse<(u16, RoomXY)>> = BinaryHeap::new();
+
+    // The origin positions are trivially reachable from the set of origin
+    // positions
+    for current_position in origins {
+        output_cm.set(*current_position, 0);
+        heap.push(Reverse((0, *current_position)));
+    }
+
+    // Always expand the cheapest known position next, so that each position is
+    // finalized the first time it's popped
+    while let Some(Reverse((current_distance, current_position))) = heap.pop() {
+        // Skip stale entries for positions that have since been reached more
+        // cheaply
+        if current_distance > output_cm.get(current_position) {
+            continue;
+        }
+
+        Direction::iter()
+            .filter_map(|dir| current_position.checked_add_direction(*dir))
+            .for_each(|position| {
+                let step_cost = costs.get(position);
+                if step_cost == u16::MAX {
+                    return;
+                }
+
+                // u16::MAX is reserved to mark unreachable positions
+                match current_distance.checked_add(step_cost) {
+                    Some(neighbor_distance)
+                        if neighbor_distance <= max_distance
+                            && neighbor_distance < output_cm.get(position) =>
+                    {
+                        output_cm.set(position, neighbor_distance);
+                        heap.push(Reverse((neighbor_distance, position)));
+                    }
+                    _ => (),
+                }
+            });
+    }
+
+    output_cm
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    #[test]
+    fn weighted_floodfill_matches_numerical_with_unit_costs() {
+        let mut bits = Box::new([0; 2500]);
+        // a short wall segment across part of the room
+        for x in 5..20 {
+            bits[10 * 50 + x] = 1;
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        let origins = vec![make_xy(10, 5)];
+
+        let obstacles = get_obstacles_lcm_from_terrain(&terrain);
+        let costs = get_move_costs_from_terrain(&terrain, 1, 1);
+
+        assert_eq!(
+            numerical_floodfill(&origins, &obstacles, u16::MAX),
+            weighted_floodfill(&origins, &costs, u16::MAX)
+        );
+    }
+
+    #[test]
+    fn weighted_floodfill_prefers_cheaper_tiles() {
+        let mut bits = Box::new([0; 2500]);
+        // swamp on the direct line between the origin and target
+        for x in 11..14 {
+            bits[10 * 50 + x] = 2;
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        let origins = vec![make_xy(10, 10)];
+        let costs = get_move_costs_from_terrain(&terrain, 1, 5);
+
+        let output = weighted_floodfill(&origins, &costs, u16::MAX);
+        assert_eq!(output.get(make_xy(10, 10)), 0);
+        // stepping directly onto the swamp costs the full swamp cost
+        assert_eq!(output.get(make_xy(11, 10)), 5);
+        // going around the swamp is cheaper than crossing it
+        assert_eq!(output.get(make_xy(14, 10)), 4);
+    }
+
+    #[test]
+    fn weighted_floodfill_respects_max_distance_and_obstacles() {
+        let mut costs = LocalCostMatrix::new();
+        for (_, cm_val) in costs.iter_mut() {
+            *cm_val = 2;
+        }
+        costs.set(make_xy(1, 0), u8::MAX);
+        let costs = LargeCostMatrix::from(&costs);
+        let origins = vec![make_xy(0, 0)];
+
+        let output = weighted_floodfill(&origins, &costs, 4);
+        assert_eq!(output.get(make_xy(1, 0)), u16::MAX);
+        assert_eq!(output.get(make_xy(1, 1)), 2);
+        assert_eq!(output.get(make_xy(2, 0)), 4);
+        assert_eq!(output.get(make_xy(3, 0)), u16::MAX);
+    }
+}
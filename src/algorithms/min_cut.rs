@@ -0,0 +1,329 @@
+use std::collections::VecDeque;
+
+use screeps::{
+    constants::Direction,
+    local::{linear_index_to_xy, xy_to_linear_index, LocalCostMatrix, LocalRoomTerrain, RoomXY},
+};
+
+use crate::{
+    algorithms::floodfill::get_obstacles_lcm_from_terrain, large_cost_matrix::ROOM_AREA,
+    placement::buildable,
+};
+
+// Capacity used for edges which should never be cut; larger than any possible
+// number of ramparts in a room.
+const INFINITE_CAPACITY: u32 = ROOM_AREA as u32 + 1;
+
+// Each position is split into an "in" node and an "out" node, joined by an
+// edge with the capacity of the position itself, followed by the source and
+// sink nodes.
+const SOURCE: usize = 2 * ROOM_AREA;
+const SINK: usize = 2 * ROOM_AREA + 1;
+
+#[inline]
+fn in_node(xy: RoomXY) -> usize {
+    2 * xy_to_linear_index(xy)
+}
+
+#[inline]
+fn out_node(xy: RoomXY) -> usize {
+    2 * xy_to_linear_index(xy) + 1
+}
+
+struct Edge {
+    to: usize,
+    capacity: u32,
+}
+
+// A flow network solved with Dinic's algorithm; each edge is stored next to
+// its reverse edge, so `idx ^ 1` is the reverse of edge `idx`.
+struct FlowNetwork {
+    edges: Vec<Edge>,
+    adjacency: Vec<Vec<usize>>,
+    levels: Vec<i32>,
+    next_edge: Vec<usize>,
+}
+
+impl FlowNetwork {
+    fn new(nodes: usize) -> Self {
+        FlowNetwork {
+            edges: Vec::new(),
+            adjacency: vec![Vec::new(); nodes],
+            levels: vec![-1; nodes],
+            next_edge: vec![0; nodes],
+        }
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, capacity: u32) {
+        self.adjacency[from].push(self.edges.len());
+        self.edges.push(Edge { to, capacity });
+        self.adjacency[to].push(self.edges.len());
+        self.edges.push(Edge {
+            to: from,
+            capacity: 0,
+        });
+    }
+
+    // Assigns the distance from the source to each node through edges with
+    // remaining capacity, returning whether the sink can still be reached.
+    fn build_levels(&mut self) -> bool {
+        self.levels.iter_mut().for_each(|level| *level = -1);
+        self.levels[SOURCE] = 0;
+        let mut queue = VecDeque::from([SOURCE]);
+        while let Some(node) = queue.pop_front() {
+            for &edge_idx in &self.adjacency[node] {
+                let edge = &self.edges[edge_idx];
+                if edge.capacity > 0 && self.levels[edge.to] < 0 {
+                    self.levels[edge.to] = self.levels[node] + 1;
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+        self.levels[SINK] >= 0
+    }
+
+    // Pushes a blocking flow from the source to the sink along edges between
+    // successive levels.
+    fn push_flow(&mut self, node: usize, limit: u32) -> u32 {
+        if node == SINK {
+            return limit;
+        }
+        while self.next_edge[node] < self.adjacency[node].len() {
+            let edge_idx = self.adjacency[node][self.next_edge[node]];
+            let Edge { to, capacity } = self.edges[edge_idx];
+            if capacity > 0 && self.levels[to] == self.levels[node] + 1 {
+                let pushed = self.push_flow(to, limit.min(capacity));
+                if pushed > 0 {
+                    self.edges[edge_idx].capacity -= pushed;
+                    self.edges[edge_idx ^ 1].capacity += pushed;
+                    return pushed;
+                }
+            }
+            self.next_edge[node] += 1;
+        }
+        0
+    }
+
+    fn max_flow(&mut self) -> u32 {
+        let mut flow = 0;
+        while self.build_levels() {
+            self.next_edge.iter_mut().for_each(|next| *next = 0);
+            loop {
+                let pushed = self.push_flow(SOURCE, INFINITE_CAPACITY);
+                if pushed == 0 {
+                    break;
+                }
+                flow += pushed;
+                if flow >= INFINITE_CAPACITY {
+                    return flow;
+                }
+            }
+        }
+        flow
+    }
+
+    // Finds which nodes are still reachable from the source through edges with
+    // remaining capacity; only meaningful after `max_flow`.
+    fn source_side(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.adjacency.len()];
+        reachable[SOURCE] = true;
+        let mut queue = VecDeque::from([SOURCE]);
+        while let Some(node) = queue.pop_front() {
+            for &edge_idx in &self.adjacency[node] {
+                let edge = &self.edges[edge_idx];
+                if edge.capacity > 0 && !reachable[edge.to] {
+                    reachable[edge.to] = true;
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+        reachable
+    }
+}
+
+/// Calculates the minimum set of positions which need ramparts (or walls) so
+/// that no path leads from any of the exit positions to the protected
+/// positions, treating terrain walls as the only obstacles.
+///
+/// See [`min_cut_from_cost_matrix`] for details.
+pub fn min_cut_from_terrain(
+    room_terrain: &LocalRoomTerrain,
+    protected: &[RoomXY],
+    exits: &[RoomXY],
+) -> Option<Vec<RoomXY>> {
+    min_cut_from_cost_matrix(
+        &get_obstacles_lcm_from_terrain(room_terrain),
+        protected,
+        exits,
+    )
+}
+
+/// Calculates the minimum set of positions which need ramparts (or walls) so
+/// that no path leads from any of the exit positions to the protected
+/// positions.
+///
+/// The obstacles Cost Matrix should have u8::MAX set on all positions that are
+/// obstacles, and 0 everywhere else. The protected positions would usually be
+/// rectangles around the core of a base (see
+/// [`GridIter`](crate::room_xy::GridIter)), padded out to keep attackers at a
+/// distance; they are never chosen for ramparts themselves.
+///
+/// As the game doesn't allow ramparts to be built on room edges or next to
+/// exits, those positions are treated as reachable from the exits. If the
+/// protected positions can't be sealed off, such as when they're next to an
+/// exit, returns `None`.
+pub fn min_cut_from_cost_matrix(
+    obstacles: &LocalCostMatrix,
+    protected: &[RoomXY],
+    exits: &[RoomXY],
+) -> Option<Vec<RoomXY>> {
+    let mut is_protected = [false; ROOM_AREA];
+    for xy in protected {
+        is_protected[xy_to_linear_index(*xy)] = true;
+    }
+    let mut is_exit = [false; ROOM_AREA];
+    for xy in exits {
+        is_exit[xy_to_linear_index(*xy)] = true;
+    }
+
+    let neighbors = |xy: RoomXY| {
+        Direction::iter()
+            .filter_map(move |dir| xy.checked_add_direction(*dir))
+            .filter(|neighbor| obstacles.get(*neighbor) != u8::MAX)
+    };
+
+    let mut network = FlowNetwork::new(2 * ROOM_AREA + 2);
+    for (xy, val) in obstacles.iter() {
+        if val == u8::MAX {
+            continue;
+        }
+        let idx = xy_to_linear_index(xy);
+
+        let capacity = if buildable(xy, obstacles) && !is_protected[idx] {
+            1
+        } else {
+            INFINITE_CAPACITY
+        };
+        network.add_edge(in_node(xy), out_node(xy), capacity);
+
+        if is_exit[idx] {
+            network.add_edge(SOURCE, in_node(xy), INFINITE_CAPACITY);
+        }
+        if is_protected[idx] {
+            network.add_edge(out_node(xy), SINK, INFINITE_CAPACITY);
+        }
+        for neighbor in neighbors(xy) {
+            network.add_edge(out_node(xy), in_node(neighbor), INFINITE_CAPACITY);
+        }
+    }
+
+    if network.max_flow() >= INFINITE_CAPACITY {
+        return None;
+    }
+
+    // The cut runs through the positions which can still be entered from the
+    // exits, but not passed through
+    let source_side = network.source_side();
+    Some(
+        (0..ROOM_AREA)
+            .filter(|idx| source_side[2 * idx] && !source_side[2 * idx + 1])
+            .map(linear_index_to_xy)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use screeps::constants::ExitDirection;
+
+    use crate::{
+        algorithms::floodfill::reachability_floodfill,
+        map::exit_positions,
+        room_xy::{GridIter, Order},
+    };
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    fn assert_sealed(
+        obstacles: &LocalCostMatrix,
+        protected: &[RoomXY],
+        exits: &[RoomXY],
+        cut: &[RoomXY],
+    ) {
+        let mut blocked = obstacles.clone();
+        for xy in cut {
+            blocked.set(*xy, u8::MAX);
+        }
+        let reachable = reachability_floodfill(&exits.to_vec(), &blocked);
+        assert!(protected.iter().all(|xy| reachable.get(*xy) == 0));
+    }
+
+    #[test]
+    fn min_cut_open_room() {
+        let mut bits = Box::new([0; ROOM_AREA]);
+        // only the left side of the room has exits
+        for i in 0..50 {
+            bits[i] = 1;
+            bits[49 * 50 + i] = 1;
+            bits[i * 50 + 49] = 1;
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        let exits = exit_positions(&terrain, ExitDirection::Left);
+        let protected: Vec<_> =
+            GridIter::new(make_xy(20, 20), make_xy(29, 29), Order::XMajor).collect();
+
+        let cut = min_cut_from_terrain(&terrain, &protected, &exits).unwrap();
+        // surrounding the protected area is cheaper than walling off the exit
+        assert_eq!(cut.len(), 44);
+        assert!(cut.iter().all(|xy| !protected.contains(xy)));
+        assert_sealed(
+            &get_obstacles_lcm_from_terrain(&terrain),
+            &protected,
+            &exits,
+            &cut,
+        );
+
+        // with a larger protected area, walling off the exit is cheapest
+        let protected: Vec<_> =
+            GridIter::new(make_xy(10, 5), make_xy(40, 45), Order::XMajor).collect();
+        let cut = min_cut_from_terrain(&terrain, &protected, &exits).unwrap();
+        assert_eq!(cut.len(), 48);
+        assert!(cut.iter().all(|xy| xy.x.u8() == 2));
+    }
+
+    #[test]
+    fn min_cut_uses_terrain_chokepoint() {
+        let mut bits = Box::new([0; ROOM_AREA]);
+        // a wall down the room at x = 10 with a 3 wide gap
+        for y in 0..50 {
+            if !(20..23).contains(&y) {
+                bits[y * 50 + 10] = 1;
+            }
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        let exits: Vec<_> = (1..49).map(|y| make_xy(0, y)).collect();
+        let protected: Vec<_> =
+            GridIter::new(make_xy(30, 20), make_xy(35, 25), Order::XMajor).collect();
+
+        let cut = min_cut_from_terrain(&terrain, &protected, &exits).unwrap();
+        assert_eq!(cut.len(), 3);
+        assert_sealed(
+            &get_obstacles_lcm_from_terrain(&terrain),
+            &protected,
+            &exits,
+            &cut,
+        );
+    }
+
+    #[test]
+    fn min_cut_impossible_next_to_exit() {
+        let terrain = LocalRoomTerrain::new_from_bits(Box::new([0; ROOM_AREA]));
+        let exits: Vec<_> = (1..49).map(|y| make_xy(0, y)).collect();
+        let protected = [make_xy(1, 10)];
+        assert!(min_cut_from_terrain(&terrain, &protected, &exits).is_none());
+    }
+}
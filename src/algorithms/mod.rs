@@ -1,2 +1,6 @@
+pub mod connected_components;
 pub mod distance_transform;
 pub mod floodfill;
+pub mod min_cut;
+pub mod pathfinding;
+pub mod stamp_placement;
@@ -0,0 +1,750 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap},
+};
+
+use screeps::{
+    constants::{Direction, Terrain},
+    local::{
+        linear_index_to_xy, xy_to_linear_index, LocalCostMatrix, LocalRoomTerrain, Position,
+        RoomName, RoomXY,
+    },
+    traits::CostMatrixGet,
+};
+
+use crate::{large_cost_matrix::ROOM_AREA, offline_map::OfflineShardData};
+
+/// A position to search for, along with the range to it which is acceptable
+/// for the search to complete.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchGoal {
+    pub pos: RoomXY,
+    pub range: u8,
+}
+
+impl SearchGoal {
+    pub const fn new(pos: RoomXY, range: u8) -> Self {
+        SearchGoal { pos, range }
+    }
+
+    /// Whether the given position is within range of this goal.
+    #[inline]
+    pub fn is_satisfied_by(&self, xy: RoomXY) -> bool {
+        xy.get_range_to(self.pos) <= self.range
+    }
+
+    /// The number of steps still required to get from the given position to
+    /// within range of this goal.
+    #[inline]
+    pub fn remaining_range(&self, xy: RoomXY) -> u8 {
+        xy.get_range_to(self.pos).saturating_sub(self.range)
+    }
+}
+
+/// Options controlling a native pathfinding search, following the naming and
+/// meaning of the options for the game's `PathFinder.search`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchOptions {
+    /// Cost used for any position which has a value of 0 in the cost matrix.
+    pub default_cost: u8,
+    /// Maximum number of positions the search will expand before giving up.
+    pub max_ops: u32,
+    /// Maximum total cost of a returned path.
+    pub max_cost: u32,
+    /// Weight applied to the heuristic; values above 1 make the search faster
+    /// at the expense of no longer guaranteeing the cheapest path.
+    pub heuristic_weight: f64,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            default_cost: 1,
+            max_ops: 2000,
+            max_cost: u32::MAX,
+            heuristic_weight: 1.,
+        }
+    }
+}
+
+/// The results of a native pathfinding search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchResults {
+    /// The positions of the path, not including the starting position.
+    pub path: Vec<RoomXY>,
+    /// The number of positions expanded during the search.
+    pub ops: u32,
+    /// The total cost of the path.
+    pub cost: u32,
+    /// Whether the search failed to reach any goal; if so, the path leads to
+    /// the position found that was closest to a goal.
+    pub incomplete: bool,
+}
+
+/// Searches for the cheapest path within a single room from `start` to within
+/// range of any of the `goals`, using an A* search.
+///
+/// The cost matrix holds the cost of stepping onto each position, with u8::MAX
+/// set on all positions that are obstacles. As with the game's `PathFinder`, a
+/// value of 0 means the position has no explicit cost, and
+/// [`SearchOptions::default_cost`] is used instead; include terrain costs in
+/// the matrix if they need to be considered.
+pub fn search<C>(
+    start: RoomXY,
+    goals: &[SearchGoal],
+    costs: &mut C,
+    options: SearchOptions,
+) -> SearchResults
+where
+    C: CostMatrixGet,
+{
+    let heuristic = |xy: RoomXY| -> u32 {
+        let remaining = goals
+            .iter()
+            .map(|goal| goal.remaining_range(xy))
+            .min()
+            .unwrap_or(0);
+        (remaining as f64 * options.heuristic_weight) as u32
+    };
+    let is_goal = |xy: RoomXY| goals.iter().any(|goal| goal.is_satisfied_by(xy));
+
+    let mut g_costs = vec![u32::MAX; ROOM_AREA];
+    let mut parents = vec![usize::MAX; ROOM_AREA];
+    let mut closed = vec![false; ROOM_AREA];
+    let mut open: BinaryHeap<Reverse<(u32, u32, RoomXY)>> = BinaryHeap::new();
+
+    let start_idx = xy_to_linear_index(start);
+    g_costs[start_idx] = 0;
+    open.push(Reverse((heuristic(start), 0, start)));
+
+    // Track the closest position to a goal, in case none can be reached
+    let mut best = (heuristic(start), 0, start);
+    let mut found = None;
+    let mut ops = 0;
+
+    if goals.is_empty() {
+        found = Some(start);
+    }
+
+    while found.is_none() && ops < options.max_ops {
+        let Some(Reverse((_, current_cost, current_position))) = open.pop() else {
+            break;
+        };
+        let current_idx = xy_to_linear_index(current_position);
+        if closed[current_idx] {
+            continue;
+        }
+        closed[current_idx] = true;
+
+        if is_goal(current_position) {
+            found = Some(current_position);
+            break;
+        }
+        ops += 1;
+
+        let current_heuristic = heuristic(current_position);
+        if (current_heuristic, current_cost) < (best.0, best.1) {
+            best = (current_heuristic, current_cost, current_position);
+        }
+
+        for position in
+            Direction::iter().filter_map(|dir| current_position.checked_add_direction(*dir))
+        {
+            let idx = xy_to_linear_index(position);
+            if closed[idx] {
+                continue;
+            }
+
+            let step_cost = match costs.get_xy(position) {
+                u8::MAX => continue,
+                0 => options.default_cost,
+                cost => cost,
+            };
+            let neighbor_cost = current_cost.saturating_add(step_cost as u32);
+            if neighbor_cost > options.max_cost || neighbor_cost >= g_costs[idx] {
+                continue;
+            }
+
+            g_costs[idx] = neighbor_cost;
+            parents[idx] = current_idx;
+            open.push(Reverse((
+                neighbor_cost.saturating_add(heuristic(position)),
+                neighbor_cost,
+                position,
+            )));
+        }
+    }
+
+    let (end, incomplete) = match found {
+        Some(end) => (end, false),
+        None => (best.2, true),
+    };
+
+    let mut path = Vec::new();
+    let mut idx = xy_to_linear_index(end);
+    while idx != start_idx {
+        path.push(linear_index_to_xy(idx));
+        idx = parents[idx];
+    }
+    path.reverse();
+
+    SearchResults {
+        path,
+        ops,
+        cost: g_costs[xy_to_linear_index(end)],
+        incomplete,
+    }
+}
+
+/// A position in any room to search for, along with the range to it which is
+/// acceptable for a multi-room search to complete.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MultiRoomSearchGoal {
+    pub pos: Position,
+    pub range: u32,
+}
+
+impl MultiRoomSearchGoal {
+    pub const fn new(pos: Position, range: u32) -> Self {
+        MultiRoomSearchGoal { pos, range }
+    }
+
+    /// Whether the given position is within range of this goal.
+    #[inline]
+    pub fn is_satisfied_by(&self, pos: Position) -> bool {
+        pos.get_range_to(self.pos) <= self.range
+    }
+
+    /// The number of steps still required to get from the given position to
+    /// within range of this goal.
+    #[inline]
+    pub fn remaining_range(&self, pos: Position) -> u32 {
+        pos.get_range_to(self.pos).saturating_sub(self.range)
+    }
+}
+
+/// The result of the room callback for a multi-room search, deciding how a
+/// room is searched.
+#[derive(Clone, Debug, Default)]
+pub enum MultiRoomCostResult {
+    /// Use the provided cost matrix on top of the room's terrain.
+    CostMatrix(Box<LocalCostMatrix>),
+    /// Don't search through the room at all.
+    Impassable,
+    /// Use only the room's terrain.
+    #[default]
+    Default,
+}
+
+/// Options controlling a native multi-room pathfinding search, following the
+/// naming and meaning of the options for the game's `PathFinder.search`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MultiRoomSearchOptions {
+    /// Cost for plain terrain positions with no cost matrix value set.
+    pub plain_cost: u8,
+    /// Cost for swamp terrain positions with no cost matrix value set.
+    pub swamp_cost: u8,
+    /// Maximum number of rooms which will be considered by the search.
+    pub max_rooms: u8,
+    /// Maximum number of positions the search will expand before giving up.
+    pub max_ops: u32,
+    /// Maximum total cost of a returned path.
+    pub max_cost: u32,
+    /// Weight applied to the heuristic; values above 1 make the search faster
+    /// at the expense of no longer guaranteeing the cheapest path.
+    pub heuristic_weight: f64,
+}
+
+impl Default for MultiRoomSearchOptions {
+    fn default() -> Self {
+        MultiRoomSearchOptions {
+            plain_cost: 1,
+            swamp_cost: 5,
+            max_rooms: 16,
+            max_ops: 2000,
+            max_cost: u32::MAX,
+            heuristic_weight: 1.,
+        }
+    }
+}
+
+/// The results of a native multi-room pathfinding search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiRoomSearchResults {
+    /// The positions of the path, not including the starting position.
+    pub path: Vec<Position>,
+    /// The number of positions expanded during the search.
+    pub ops: u32,
+    /// The total cost of the path.
+    pub cost: u32,
+    /// Whether the search failed to reach any goal; if so, the path leads to
+    /// the position found that was closest to a goal.
+    pub incomplete: bool,
+}
+
+struct SearchRoom<'a> {
+    terrain: &'a LocalRoomTerrain,
+    costs: Option<LocalCostMatrix>,
+}
+
+impl SearchRoom<'_> {
+    fn step_cost(&self, xy: RoomXY, options: &MultiRoomSearchOptions) -> Option<u8> {
+        let terrain = self.terrain.get_xy(xy);
+        if terrain == Terrain::Wall {
+            return None;
+        }
+        match self.costs.as_ref().map(|costs| costs.get(xy)) {
+            Some(u8::MAX) => None,
+            Some(0) | None => Some(match terrain {
+                Terrain::Swamp => options.swamp_cost,
+                _ => options.plain_cost,
+            }),
+            Some(cost) => Some(cost),
+        }
+    }
+}
+
+/// Searches for the cheapest path from `start` to within range of any of the
+/// `goals`, across the rooms of the provided shard data, using an A* search.
+///
+/// Terrain walls are always impassable, and rooms which aren't present in the
+/// shard data can't be entered. The room callback is called once for each
+/// room the search considers, and can provide a cost matrix for the room or
+/// mark it as impassable; as with the game's `PathFinder`, cost matrix values
+/// of 0 fall back to the terrain cost and u8::MAX marks an obstacle.
+///
+/// As in the game, room borders can only be crossed by moving straight from an
+/// edge position to the matching edge position of the neighbouring room, and
+/// paths never move along the edge of a room, as stepping onto an edge
+/// position from inside the room always crosses the border.
+pub fn multi_room_search<F>(
+    shard_data: &OfflineShardData,
+    start: Position,
+    goals: &[MultiRoomSearchGoal],
+    mut room_callback: F,
+    options: MultiRoomSearchOptions,
+) -> MultiRoomSearchResults
+where
+    F: FnMut(RoomName) -> MultiRoomCostResult,
+{
+    let heuristic = |pos: Position| -> u32 {
+        let remaining = goals
+            .iter()
+            .map(|goal| goal.remaining_range(pos))
+            .min()
+            .unwrap_or(0);
+        (remaining as f64 * options.heuristic_weight) as u32
+    };
+    let is_goal = |pos: Position| goals.iter().any(|goal| goal.is_satisfied_by(pos));
+
+    let mut rooms: HashMap<RoomName, Option<SearchRoom>> = HashMap::new();
+    let mut rooms_searched = 0;
+    // the cheapest known cost to each position, along with the position it was
+    // reached from, and whether it's been fully expanded
+    let mut nodes: HashMap<Position, (u32, Option<Position>, bool)> = HashMap::new();
+    let mut open: BinaryHeap<Reverse<(u32, u32, Position)>> = BinaryHeap::new();
+
+    nodes.insert(start, (0, None, false));
+    open.push(Reverse((heuristic(start), 0, start)));
+
+    // Track the closest position to a goal, in case none can be reached
+    let mut best = (heuristic(start), 0, start);
+    let mut found = None;
+    let mut ops = 0;
+
+    if goals.is_empty() {
+        found = Some(start);
+    }
+
+    while found.is_none() && ops < options.max_ops {
+        let Some(Reverse((_, current_cost, current_position))) = open.pop() else {
+            break;
+        };
+        let current_parent = match nodes.get_mut(&current_position) {
+            Some((_, parent, closed)) if !*closed => {
+                *closed = true;
+                *parent
+            }
+            _ => continue,
+        };
+
+        if is_goal(current_position) {
+            found = Some(current_position);
+            break;
+        }
+        ops += 1;
+
+        let current_heuristic = heuristic(current_position);
+        if (current_heuristic, current_cost) < (best.0, best.1) {
+            best = (current_heuristic, current_cost, current_position);
+        }
+
+        // A creep stepping onto an exit from inside the room is moved straight
+        // across the border, while one that has just arrived on an edge can
+        // only move into the room
+        let on_edge = current_position.xy().is_room_edge();
+        let arrived =
+            current_parent.is_none_or(|parent| parent.room_name() != current_position.room_name());
+
+        for dir in Direction::iter() {
+            let Ok(position) = current_position.checked_add_direction(*dir) else {
+                continue;
+            };
+            let room_name = position.room_name();
+            let crosses_border = room_name != current_position.room_name();
+            if crosses_border && !dir.is_orthogonal() {
+                continue;
+            }
+            if on_edge {
+                let allowed = if arrived {
+                    !crosses_border && !position.xy().is_room_edge()
+                } else {
+                    crosses_border
+                };
+                if !allowed {
+                    continue;
+                }
+            }
+
+            let room = match rooms.entry(room_name) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let room = match shard_data.rooms.get(&room_name) {
+                        Some(room_data) if rooms_searched < options.max_rooms => {
+                            rooms_searched += 1;
+                            match room_callback(room_name) {
+                                MultiRoomCostResult::CostMatrix(costs) => Some(SearchRoom {
+                                    terrain: &room_data.terrain,
+                                    costs: Some(*costs),
+                                }),
+                                MultiRoomCostResult::Impassable => None,
+                                MultiRoomCostResult::Default => Some(SearchRoom {
+                                    terrain: &room_data.terrain,
+                                    costs: None,
+                                }),
+                            }
+                        }
+                        _ => None,
+                    };
+                    entry.insert(room)
+                }
+            };
+            let Some(step_cost) = room
+                .as_ref()
+                .and_then(|room| room.step_cost(position.xy(), &options))
+            else {
+                continue;
+            };
+
+            let neighbor_cost = current_cost.saturating_add(step_cost as u32);
+            if neighbor_cost > options.max_cost {
+                continue;
+            }
+            match nodes.entry(position) {
+                Entry::Occupied(mut entry) => {
+                    let (known_cost, parent, closed) = entry.get_mut();
+                    if *closed || neighbor_cost >= *known_cost {
+                        continue;
+                    }
+                    *known_cost = neighbor_cost;
+                    *parent = Some(current_position);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert((neighbor_cost, Some(current_position), false));
+                }
+            }
+            open.push(Reverse((
+                neighbor_cost.saturating_add(heuristic(position)),
+                neighbor_cost,
+                position,
+            )));
+        }
+    }
+
+    let (end, incomplete) = match found {
+        Some(end) => (end, false),
+        None => (best.2, true),
+    };
+
+    let mut path = Vec::new();
+    let mut current = end;
+    while let Some((_, Some(parent), _)) = nodes.get(&current) {
+        path.push(current);
+        current = *parent;
+    }
+    path.reverse();
+
+    MultiRoomSearchResults {
+        path,
+        ops,
+        cost: nodes.get(&end).map(|(cost, _, _)| *cost).unwrap_or(0),
+        incomplete,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use screeps::{game::map::RoomStatus, local::RoomCoordinate};
+
+    use crate::{
+        large_cost_matrix::LargeCostMatrix, offline_map::OfflineRoomData,
+        sparse_cost_matrix::SparseCostMatrix,
+    };
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    #[test]
+    fn straight_line_path() {
+        let mut costs = LocalCostMatrix::new();
+        let results = search(
+            make_xy(10, 10),
+            &[SearchGoal::new(make_xy(20, 10), 0)],
+            &mut costs,
+            SearchOptions::default(),
+        );
+        assert!(!results.incomplete);
+        assert_eq!(results.path.len(), 10);
+        assert_eq!(results.cost, 10);
+        assert_eq!(results.path.last(), Some(&make_xy(20, 10)));
+    }
+
+    #[test]
+    fn path_stops_in_range() {
+        let mut costs = SparseCostMatrix::new();
+        let results = search(
+            make_xy(10, 10),
+            &[SearchGoal::new(make_xy(20, 20), 3)],
+            &mut costs,
+            SearchOptions::default(),
+        );
+        assert!(!results.incomplete);
+        assert_eq!(results.path.len(), 7);
+        assert_eq!(
+            results.path.last().unwrap().get_range_to(make_xy(20, 20)),
+            3
+        );
+    }
+
+    #[test]
+    fn path_avoids_obstacles_and_expensive_positions() {
+        let mut costs = LargeCostMatrix::new();
+        // a wall with a single gap at the bottom, and swamp in the gap
+        for y in 0..49 {
+            costs.set(make_xy(25, y), u16::MAX);
+        }
+        costs.set(make_xy(25, 48), 5);
+
+        let results = search(
+            make_xy(20, 45),
+            &[SearchGoal::new(make_xy(30, 45), 0)],
+            &mut costs,
+            SearchOptions::default(),
+        );
+        assert!(!results.incomplete);
+        assert!(results.path.contains(&make_xy(25, 49)));
+        assert_eq!(results.cost, 10);
+        for window in results.path.windows(2) {
+            assert!(window[0].is_near_to(window[1]));
+        }
+    }
+
+    #[test]
+    fn unreachable_goal_is_incomplete() {
+        let mut costs = LocalCostMatrix::new();
+        for xy in make_xy(30, 30).neighbors() {
+            costs.set(xy, u8::MAX);
+        }
+        let results = search(
+            make_xy(10, 10),
+            &[SearchGoal::new(make_xy(30, 30), 0)],
+            &mut costs,
+            SearchOptions {
+                max_ops: 10_000,
+                ..Default::default()
+            },
+        );
+        assert!(results.incomplete);
+        assert_eq!(
+            results.path.last().unwrap().get_range_to(make_xy(30, 30)),
+            2
+        );
+    }
+
+    #[test]
+    fn start_within_range() {
+        let mut costs = LocalCostMatrix::new();
+        let results = search(
+            make_xy(10, 10),
+            &[SearchGoal::new(make_xy(11, 11), 1)],
+            &mut costs,
+            SearchOptions::default(),
+        );
+        assert!(!results.incomplete);
+        assert!(results.path.is_empty());
+        assert_eq!(results.cost, 0);
+    }
+
+    fn make_room(name: &str, bits: Box<[u8; ROOM_AREA]>) -> OfflineRoomData {
+        OfflineRoomData {
+            room_name: RoomName::new(name).unwrap(),
+            status: RoomStatus::Normal,
+            bus: false,
+            terrain: LocalRoomTerrain::new_from_bits(bits),
+            objects: Vec::new(),
+        }
+    }
+
+    fn make_shard(rooms: Vec<OfflineRoomData>) -> OfflineShardData {
+        OfflineShardData {
+            description: String::new(),
+            rooms: rooms
+                .into_iter()
+                .map(|room| (room.room_name, room))
+                .collect(),
+        }
+    }
+
+    fn make_pos(x: u8, y: u8, room: &str) -> Position {
+        Position::new(
+            RoomCoordinate::new(x).unwrap(),
+            RoomCoordinate::new(y).unwrap(),
+            RoomName::new(room).unwrap(),
+        )
+    }
+
+    #[test]
+    fn multi_room_path_crosses_border() {
+        let shard = make_shard(vec![
+            make_room("W0N1", Box::new([0; ROOM_AREA])),
+            make_room("E0N1", Box::new([0; ROOM_AREA])),
+        ]);
+        let start = make_pos(40, 25, "W0N1");
+        let goal = make_pos(10, 25, "E0N1");
+
+        let results = multi_room_search(
+            &shard,
+            start,
+            &[MultiRoomSearchGoal::new(goal, 0)],
+            |_| MultiRoomCostResult::Default,
+            MultiRoomSearchOptions::default(),
+        );
+        assert!(!results.incomplete);
+        assert_eq!(results.path.len(), 20);
+        assert_eq!(results.cost, 20);
+        let crossings: Vec<_> = results
+            .path
+            .windows(2)
+            .filter(|window| window[0].room_name() != window[1].room_name())
+            .collect();
+        assert_eq!(crossings.len(), 1);
+        assert_eq!(crossings[0][0].x().u8(), 49);
+        assert_eq!(crossings[0][1].x().u8(), 0);
+        assert_eq!(crossings[0][0].y(), crossings[0][1].y());
+    }
+
+    #[test]
+    fn multi_room_path_cant_walk_along_exits() {
+        let mut bits = Box::new([1; ROOM_AREA]);
+        // the only open positions are the start and end, and the exits
+        // between them on the room's edge
+        bits[10 * 50 + 48] = 0;
+        bits[40 * 50 + 48] = 0;
+        for y in 11..=39 {
+            bits[y * 50 + 49] = 0;
+        }
+        let shard = make_shard(vec![make_room("W1N1", bits)]);
+
+        let results = multi_room_search(
+            &shard,
+            make_pos(48, 10, "W1N1"),
+            &[MultiRoomSearchGoal::new(make_pos(48, 40, "W1N1"), 0)],
+            |_| MultiRoomCostResult::Default,
+            MultiRoomSearchOptions {
+                max_ops: 10_000,
+                ..Default::default()
+            },
+        );
+        assert!(results.incomplete);
+        assert!(results.path.len() <= 1);
+    }
+
+    #[test]
+    fn multi_room_path_respects_room_callback_and_missing_rooms() {
+        let shard = make_shard(vec![
+            make_room("W0N1", Box::new([0; ROOM_AREA])),
+            make_room("E0N1", Box::new([0; ROOM_AREA])),
+        ]);
+        let start = make_pos(40, 25, "W0N1");
+        let goal = make_pos(10, 25, "E0N1");
+
+        let results = multi_room_search(
+            &shard,
+            start,
+            &[MultiRoomSearchGoal::new(goal, 0)],
+            |room_name| {
+                if room_name == RoomName::new("E0N1").unwrap() {
+                    MultiRoomCostResult::Impassable
+                } else {
+                    MultiRoomCostResult::Default
+                }
+            },
+            MultiRoomSearchOptions {
+                max_ops: 10_000,
+                ..Default::default()
+            },
+        );
+        assert!(results.incomplete);
+        let end = *results.path.last().unwrap();
+        assert_eq!(end.room_name(), RoomName::new("W0N1").unwrap());
+        assert_eq!(end.x().u8(), 49);
+
+        // the room to the north isn't in the shard data at all
+        let results = multi_room_search(
+            &shard,
+            start,
+            &[MultiRoomSearchGoal::new(make_pos(40, 25, "W0N2"), 0)],
+            |_| MultiRoomCostResult::Default,
+            MultiRoomSearchOptions {
+                max_ops: 10_000,
+                ..Default::default()
+            },
+        );
+        assert!(results.incomplete);
+    }
+
+    #[test]
+    fn multi_room_path_uses_terrain_and_cost_matrix() {
+        let mut bits = Box::new([0; ROOM_AREA]);
+        // a wall across the room with a swamp gap at y = 10, and a plain gap at
+        // y = 40 which the cost matrix blocks
+        for y in 0..50 {
+            bits[y * 50 + 25] = match y {
+                10 => 2,
+                40 => 0,
+                _ => 1,
+            };
+        }
+        let shard = make_shard(vec![make_room("W1N1", bits)]);
+        let mut costs = LocalCostMatrix::new();
+        costs.set(RoomXY::checked_new(25, 40).unwrap(), u8::MAX);
+
+        let results = multi_room_search(
+            &shard,
+            make_pos(20, 40, "W1N1"),
+            &[MultiRoomSearchGoal::new(make_pos(30, 40, "W1N1"), 0)],
+            |_| MultiRoomCostResult::CostMatrix(Box::new(costs.clone())),
+            MultiRoomSearchOptions {
+                max_ops: 10_000,
+                ..Default::default()
+            },
+        );
+        assert!(!results.incomplete);
+        assert!(results.path.contains(&make_pos(25, 10, "W1N1")));
+        // 60 steps, one of which is onto a swamp
+        assert_eq!(results.cost, 64);
+    }
+}
@@ -0,0 +1,306 @@
+use screeps::{
+    constants::{Terrain, ROOM_SIZE},
+    local::{LocalCostMatrix, LocalRoomTerrain, RoomXY},
+};
+
+use crate::algorithms::distance_transform::chebyshev_distance_transform_from_terrain;
+
+/// A set of positions relative to an anchor position, such as the layout of a
+/// group of structures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stamp {
+    offsets: Vec<(i8, i8)>,
+    radius: u8,
+}
+
+impl Stamp {
+    /// Creates a stamp from a list of `(x, y)` offsets from its anchor.
+    pub fn new(offsets: Vec<(i8, i8)>) -> Self {
+        let radius = offsets
+            .iter()
+            .map(|(x, y)| x.unsigned_abs().max(y.unsigned_abs()))
+            .max()
+            .unwrap_or(0);
+        Stamp { offsets, radius }
+    }
+
+    /// Creates a rectangular stamp of the given size, anchored at its top-left
+    /// corner.
+    pub fn rectangle(width: u8, height: u8) -> Self {
+        let width = width.min(ROOM_SIZE) as i8;
+        let height = height.min(ROOM_SIZE) as i8;
+        Stamp::new(
+            (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .collect(),
+        )
+    }
+
+    /// The offsets from the anchor of each position in the stamp.
+    pub fn offsets(&self) -> &[(i8, i8)] {
+        &self.offsets
+    }
+
+    /// The largest Chebyshev distance of any position in the stamp from its
+    /// anchor.
+    pub fn radius(&self) -> u8 {
+        self.radius
+    }
+
+    /// Gets the room positions covered by the stamp when placed at the given
+    /// anchor, or `None` if any would be outside of the room.
+    pub fn positions_at(&self, anchor: RoomXY) -> Option<Vec<RoomXY>> {
+        self.offsets
+            .iter()
+            .map(|offset| anchor.checked_add(*offset))
+            .collect()
+    }
+}
+
+/// A possible anchor position for a stamp, as found by [`find_stamp_anchors`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StampCandidate {
+    pub anchor: RoomXY,
+    /// The sum of the ranges from the stamp to each target
+    pub target_distance: u32,
+}
+
+/// Finds every anchor position at which the stamp fits entirely on non-wall
+/// terrain, ranked by their distance to the provided targets.
+///
+/// See [`find_stamp_anchors_from_distance_transform`] for details.
+pub fn find_stamp_anchors(
+    room_terrain: &LocalRoomTerrain,
+    stamp: &Stamp,
+    targets: &[RoomXY],
+) -> Vec<StampCandidate> {
+    find_stamp_anchors_from_distance_transform(
+        &chebyshev_distance_transform_from_terrain(room_terrain),
+        stamp,
+        targets,
+    )
+}
+
+/// Finds every anchor position at which the stamp fits entirely on positions
+/// with a non-zero value in the provided Chebyshev distance transform, ranked
+/// by their distance to the provided targets.
+///
+/// The distance transform would usually come from
+/// [`chebyshev_distance_transform_from_terrain`], or from one of the other
+/// Chebyshev distance transform functions to also avoid objects. Stamps are
+/// never placed on room edges, since no structures can be built there.
+///
+/// The distance of each candidate is the sum, over all targets, of the range
+/// from the closest stamp position to that target; candidates are sorted by
+/// this distance, closest first.
+pub fn find_stamp_anchors_from_distance_transform(
+    distance_transform: &LocalCostMatrix,
+    stamp: &Stamp,
+    targets: &[RoomXY],
+) -> Vec<StampCandidate> {
+    let mut candidates: Vec<StampCandidate> = distance_transform
+        .iter()
+        .filter_map(|(anchor, anchor_distance)| {
+            let positions = stamp.positions_at(anchor)?;
+            if positions.iter().any(|xy| xy.is_room_edge()) {
+                return None;
+            }
+            // If the anchor is further from any wall than the furthest stamp
+            // position, the whole stamp must fit; otherwise check each position
+            if anchor_distance <= stamp.radius()
+                && positions.iter().any(|xy| distance_transform.get(*xy) == 0)
+            {
+                return None;
+            }
+            let target_distance = targets
+                .iter()
+                .map(|target| {
+                    positions
+                        .iter()
+                        .map(|xy| xy.get_range_to(*target) as u32)
+                        .min()
+                        .unwrap_or(0)
+                })
+                .sum();
+            Some(StampCandidate {
+                anchor,
+                target_distance,
+            })
+        })
+        .collect();
+
+    candidates.sort_by_key(|candidate| (candidate.target_distance, candidate.anchor));
+    candidates
+}
+
+/// Finds the largest squares of non-wall terrain in a room, returning the
+/// length of their sides along with the top-left corner of each.
+///
+/// As with [`find_stamp_anchors_from_distance_transform`], positions on the
+/// room edges are never included.
+pub fn largest_squares(room_terrain: &LocalRoomTerrain) -> (u8, Vec<RoomXY>) {
+    let mut best = (0, Vec::new());
+    // Track the size of the largest square ending at each position of the
+    // previous row
+    let mut previous = [0_u8; ROOM_SIZE as usize];
+    for y in 0..ROOM_SIZE {
+        let mut current = [0_u8; ROOM_SIZE as usize];
+        for x in 0..ROOM_SIZE {
+            // SAFETY: both coordinates are within 0..ROOM_SIZE
+            let xy = unsafe { RoomXY::unchecked_new(x, y) };
+            if xy.is_room_edge() || room_terrain.get_xy(xy) == Terrain::Wall {
+                continue;
+            }
+            // Edge positions are skipped, so there's always a previous row
+            // and column
+            let size = previous[x as usize - 1]
+                .min(previous[x as usize])
+                .min(current[x as usize - 1])
+                + 1;
+            current[x as usize] = size;
+            if size > best.0 {
+                best = (size, Vec::new());
+            }
+            if size == best.0 {
+                // SAFETY: the square fits within the room, so its corner does too
+                best.1
+                    .push(unsafe { RoomXY::unchecked_new(x + 1 - size, y + 1 - size) });
+            }
+        }
+        previous = current;
+    }
+    best.1.sort();
+    best
+}
+
+/// Finds the largest area rectangle of non-wall terrain in a room, returning
+/// its top-left and bottom-right corners, or `None` if the room is entirely
+/// walls.
+///
+/// As with [`find_stamp_anchors_from_distance_transform`], positions on the
+/// room edges are never included.
+pub fn largest_rectangle(room_terrain: &LocalRoomTerrain) -> Option<(RoomXY, RoomXY)> {
+    let size = ROOM_SIZE as usize;
+    let mut heights = vec![0_usize; size];
+    let mut best: Option<(usize, (usize, usize, usize, usize))> = None;
+
+    for y in 0..size {
+        for (x, height) in heights.iter_mut().enumerate() {
+            // SAFETY: both coordinates are within 0..ROOM_SIZE
+            let xy = unsafe { RoomXY::unchecked_new(x as u8, y as u8) };
+            *height = match room_terrain.get_xy(xy) {
+                _ if xy.is_room_edge() => 0,
+                Terrain::Wall => 0,
+                _ => *height + 1,
+            };
+        }
+
+        // Largest rectangle in the histogram of open heights ending at this
+        // row, using a stack of increasing heights
+        let mut stack: Vec<usize> = Vec::new();
+        for x in 0..=size {
+            let height = if x < size { heights[x] } else { 0 };
+            while let Some(&top) = stack.last() {
+                if heights[top] < height {
+                    break;
+                }
+                stack.pop();
+                let left = stack.last().map(|idx| idx + 1).unwrap_or(0);
+                let area = heights[top] * (x - left);
+                if area > best.map_or(0, |(best_area, _)| best_area) {
+                    best = Some((area, (left, y + 1 - heights[top], x - 1, y)));
+                }
+            }
+            stack.push(x);
+        }
+    }
+
+    // SAFETY: all corners are within the room
+    best.map(|(_, (left, top, right, bottom))| unsafe {
+        (
+            RoomXY::unchecked_new(left as u8, top as u8),
+            RoomXY::unchecked_new(right as u8, bottom as u8),
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    fn walled_room(open_top_left: (u8, u8), open_bottom_right: (u8, u8)) -> LocalRoomTerrain {
+        let mut bits = Box::new([1; 2500]);
+        for y in open_top_left.1..=open_bottom_right.1 {
+            for x in open_top_left.0..=open_bottom_right.0 {
+                bits[y as usize * 50 + x as usize] = 0;
+            }
+        }
+        LocalRoomTerrain::new_from_bits(bits)
+    }
+
+    #[test]
+    fn rectangle_anchors() {
+        // a 6x4 open area from (10, 10) to (15, 13)
+        let terrain = walled_room((10, 10), (15, 13));
+        let candidates = find_stamp_anchors(&terrain, &Stamp::rectangle(5, 3), &[]);
+        let anchors: Vec<_> = candidates.iter().map(|c| c.anchor).collect();
+        assert_eq!(
+            anchors,
+            [
+                make_xy(10, 10),
+                make_xy(11, 10),
+                make_xy(10, 11),
+                make_xy(11, 11)
+            ]
+        );
+
+        assert!(find_stamp_anchors(&terrain, &Stamp::rectangle(7, 1), &[]).is_empty());
+    }
+
+    #[test]
+    fn anchors_ranked_by_target_distance() {
+        let terrain = walled_room((1, 1), (48, 48));
+        let stamp = Stamp::new(vec![(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)]);
+        let candidates = find_stamp_anchors(&terrain, &stamp, &[make_xy(30, 30)]);
+
+        // the stamp can't touch the room edge
+        assert_eq!(candidates.len(), 46 * 46);
+        assert_eq!(candidates[0].target_distance, 0);
+        assert!(candidates
+            .iter()
+            .take(5)
+            .all(|c| c.anchor.get_range_to(make_xy(30, 30)) <= 1));
+        assert!(candidates
+            .windows(2)
+            .all(|pair| pair[0].target_distance <= pair[1].target_distance));
+    }
+
+    #[test]
+    fn largest_open_areas() {
+        let terrain = walled_room((10, 10), (15, 13));
+        assert_eq!(
+            largest_squares(&terrain),
+            (4, vec![make_xy(10, 10), make_xy(11, 10), make_xy(12, 10)])
+        );
+        assert_eq!(
+            largest_rectangle(&terrain),
+            Some((make_xy(10, 10), make_xy(15, 13)))
+        );
+
+        // edges of an open room aren't included
+        let terrain = LocalRoomTerrain::new_from_bits(Box::new([0; 2500]));
+        assert_eq!(largest_squares(&terrain), (48, vec![make_xy(1, 1)]));
+        assert_eq!(
+            largest_rectangle(&terrain),
+            Some((make_xy(1, 1), make_xy(48, 48)))
+        );
+
+        let terrain = LocalRoomTerrain::new_from_bits(Box::new([1; 2500]));
+        assert_eq!(largest_squares(&terrain), (0, vec![]));
+        assert_eq!(largest_rectangle(&terrain), None);
+    }
+}
@@ -108,6 +108,28 @@ impl From<&CostMatrix> for LargeCostMatrix {
     }
 }
 
+impl From<&LocalCostMatrix> for LargeCostMatrix {
+    /// Widens the values of a `LocalCostMatrix`; positions with a value of
+    /// `u8::MAX` are mapped to `u16::MAX` so that they remain impassable.
+    fn from(lcm: &LocalCostMatrix) -> Self {
+        let mut bits: [u16; ROOM_AREA] = [0; ROOM_AREA];
+        lcm.iter().for_each(|(xy, val)| {
+            bits[xy_to_linear_index(xy)] = match val {
+                u8::MAX => u16::MAX,
+                _ => val.into(),
+            }
+        });
+
+        LargeCostMatrix { bits }
+    }
+}
+
+impl From<LocalCostMatrix> for LargeCostMatrix {
+    fn from(lcm: LocalCostMatrix) -> Self {
+        LargeCostMatrix::from(&lcm)
+    }
+}
+
 impl Index<RoomXY> for LargeCostMatrix {
     type Output = u16;
 
@@ -4,6 +4,8 @@ pub mod map;
 pub mod math;
 pub mod object;
 pub mod offline_map;
+mod placement;
+pub mod planning;
 pub mod room_coordinate;
 pub mod room_xy;
 pub mod sparse_cost_matrix;
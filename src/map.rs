@@ -1,6 +1,20 @@
-use screeps::local::RoomName;
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
 
-#[derive(Debug, PartialEq, Eq)]
+use screeps::{
+    constants::{ExitDirection, Terrain, ROOM_SIZE},
+    game::map::{RoomStatus, RouteStep},
+    local::{LocalRoomTerrain, RoomName, RoomXY},
+};
+
+use crate::{
+    algorithms::floodfill::{get_obstacles_lcm_from_terrain, reachability_floodfill},
+    offline_map::OfflineShardData,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum RoomType {
     Normal,
     Highway,
@@ -39,10 +53,348 @@ pub fn room_type_for_name(room_name: RoomName) -> RoomType {
     }
 }
 
+/// Gets the name of the room neighbouring the given room across the given
+/// exit, or `None` if that would be outside of the bounds of the world.
+pub fn neighbour_room_name(room_name: RoomName, exit: ExitDirection) -> Option<RoomName> {
+    let offset = match exit {
+        ExitDirection::Top => (0, -1),
+        ExitDirection::Right => (1, 0),
+        ExitDirection::Bottom => (0, 1),
+        ExitDirection::Left => (-1, 0),
+    };
+    room_name.checked_add(offset)
+}
+
+/// Iterates over the positions along the room edge for the given exit
+/// direction.
+fn edge_positions(exit: ExitDirection) -> impl Iterator<Item = RoomXY> {
+    (0..ROOM_SIZE).map(move |i| {
+        // SAFETY: both coordinates are always in 0..ROOM_SIZE
+        let (x, y) = match exit {
+            ExitDirection::Top => (i, 0),
+            ExitDirection::Right => (ROOM_SIZE - 1, i),
+            ExitDirection::Bottom => (i, ROOM_SIZE - 1),
+            ExitDirection::Left => (0, i),
+        };
+        unsafe { RoomXY::unchecked_new(x, y) }
+    })
+}
+
+/// Gets the neighbouring rooms which can be reached through an exit on each
+/// side of a room, based on the room's terrain; the equivalent of the game's
+/// `Game.map.describeExits`.
+///
+/// A side of the room has an exit if any of its edge positions aren't walls.
+pub fn describe_exits(
+    room_name: RoomName,
+    room_terrain: &LocalRoomTerrain,
+) -> HashMap<ExitDirection, RoomName> {
+    [
+        ExitDirection::Top,
+        ExitDirection::Right,
+        ExitDirection::Bottom,
+        ExitDirection::Left,
+    ]
+    .into_iter()
+    .filter(|exit| edge_positions(*exit).any(|xy| room_terrain.get_xy(xy) != Terrain::Wall))
+    .filter_map(|exit| neighbour_room_name(room_name, exit).map(|neighbour| (exit, neighbour)))
+    .collect()
+}
+
+/// A contiguous run of exit positions along one side of a room.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExitSegment {
+    /// The side of the room the exit is on
+    pub exit: ExitDirection,
+    /// The exit positions, in order along the room edge
+    pub positions: Vec<RoomXY>,
+}
+
+impl ExitSegment {
+    /// Gets the name of the room this exit leads to from the given room.
+    pub fn destination(&self, room_name: RoomName) -> Option<RoomName> {
+        neighbour_room_name(room_name, self.exit)
+    }
+}
+
+/// Gets all of the exit positions on the given side of a room; that is, all
+/// the edge positions on that side which aren't walls.
+pub fn exit_positions(room_terrain: &LocalRoomTerrain, exit: ExitDirection) -> Vec<RoomXY> {
+    edge_positions(exit)
+        .filter(|xy| room_terrain.get_xy(*xy) != Terrain::Wall)
+        .collect()
+}
+
+/// Gets the exits of a room grouped into contiguous segments, ordered by side
+/// (top, right, bottom, left) and then by position along each side.
+pub fn exit_segments(room_terrain: &LocalRoomTerrain) -> Vec<ExitSegment> {
+    let mut segments = Vec::new();
+    for exit in [
+        ExitDirection::Top,
+        ExitDirection::Right,
+        ExitDirection::Bottom,
+        ExitDirection::Left,
+    ] {
+        let mut current: Vec<RoomXY> = Vec::new();
+        for xy in edge_positions(exit) {
+            if room_terrain.get_xy(xy) == Terrain::Wall {
+                if !current.is_empty() {
+                    segments.push(ExitSegment {
+                        exit,
+                        positions: std::mem::take(&mut current),
+                    });
+                }
+            } else {
+                current.push(xy);
+            }
+        }
+        if !current.is_empty() {
+            segments.push(ExitSegment {
+                exit,
+                positions: current,
+            });
+        }
+    }
+    segments
+}
+
+/// Groups the exit segments of a room by which of them are connected to each
+/// other through the room's terrain.
+///
+/// Movement along the room edge isn't considered, since creeps stepping onto
+/// an exit position leave the room. Each group is ordered in the same way as
+/// [`exit_segments`].
+pub fn exit_groups(room_terrain: &LocalRoomTerrain) -> Vec<Vec<ExitSegment>> {
+    let mut obstacles = get_obstacles_lcm_from_terrain(room_terrain);
+    for (xy, cm_val) in obstacles.iter_mut() {
+        if xy.is_room_edge() {
+            *cm_val = u8::MAX;
+        }
+    }
+
+    let mut remaining = exit_segments(room_terrain);
+    let mut groups = Vec::new();
+    while !remaining.is_empty() {
+        let first = remaining.remove(0);
+        let reachable = reachability_floodfill(&first.positions, &obstacles);
+        let touches_reachable = |segment: &ExitSegment| {
+            segment
+                .positions
+                .iter()
+                .flat_map(|xy| xy.neighbors())
+                .any(|xy| !xy.is_room_edge() && reachable.get(xy) == 1)
+        };
+
+        let mut group = vec![first];
+        let (connected, unconnected) = remaining.into_iter().partition(touches_reachable);
+        group.extend::<Vec<_>>(connected);
+        remaining = unconnected;
+        groups.push(group);
+    }
+    groups
+}
+
+/// Whether a route can move directly between rooms with the given statuses;
+/// closed rooms can't be entered at all, and novice and respawn areas are
+/// walled off from rooms of any other status.
+fn statuses_connect(from: RoomStatus, to: RoomStatus) -> bool {
+    to != RoomStatus::Closed && from == to
+}
+
+// An entry in the open set of a route search, ordered so that the cheapest
+// room is at the top of a `BinaryHeap`.
+struct RouteNode {
+    cost: f64,
+    room_name: RoomName,
+}
+
+impl PartialEq for RouteNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RouteNode {}
+
+impl PartialOrd for RouteNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RouteNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.room_name.cmp(&self.room_name))
+    }
+}
+
+fn find_route_with<E, F>(
+    from: RoomName,
+    to: RoomName,
+    mut exits: E,
+    mut route_callback: F,
+) -> Option<Vec<RouteStep>>
+where
+    E: FnMut(RoomName) -> Vec<(ExitDirection, RoomName, RoomStatus)>,
+    F: FnMut(RoomName, RoomName, RoomType, RoomStatus) -> f64,
+{
+    let mut costs: HashMap<RoomName, f64> = HashMap::new();
+    let mut parents: HashMap<RoomName, (ExitDirection, RoomName)> = HashMap::new();
+    let mut closed: HashSet<RoomName> = HashSet::new();
+    let mut open = BinaryHeap::new();
+
+    costs.insert(from, 0.);
+    open.push(RouteNode {
+        cost: 0.,
+        room_name: from,
+    });
+
+    while let Some(RouteNode { cost, room_name }) = open.pop() {
+        if !closed.insert(room_name) {
+            continue;
+        }
+
+        if room_name == to {
+            let mut route = Vec::new();
+            let mut current = to;
+            while let Some((exit, parent)) = parents.get(&current) {
+                route.push(RouteStep {
+                    exit: *exit,
+                    room: current,
+                });
+                current = *parent;
+            }
+            route.reverse();
+            return Some(route);
+        }
+
+        for (exit, neighbour, status) in exits(room_name) {
+            if closed.contains(&neighbour) {
+                continue;
+            }
+
+            let room_cost =
+                route_callback(neighbour, room_name, room_type_for_name(neighbour), status);
+            if !room_cost.is_finite() {
+                continue;
+            }
+
+            let neighbour_cost = cost + room_cost.max(0.);
+            if costs
+                .get(&neighbour)
+                .is_some_and(|known_cost| *known_cost <= neighbour_cost)
+            {
+                continue;
+            }
+            costs.insert(neighbour, neighbour_cost);
+            parents.insert(neighbour, (exit, room_name));
+            open.push(RouteNode {
+                cost: neighbour_cost,
+                room_name: neighbour,
+            });
+        }
+    }
+
+    None
+}
+
+/// Finds the cheapest route of rooms from one room to another through the
+/// rooms of the provided shard data; the equivalent of the game's
+/// `Game.map.findRoute`.
+///
+/// Exits between rooms are determined from each room's terrain, and rooms can
+/// only be entered from rooms of the same status, with closed rooms never
+/// entered. The route callback is called with the name of the room being
+/// entered, the name of the room it's being entered from, and the entered
+/// room's type and status; it should return the cost of entering the room
+/// (1.0 is the default cost used in the game), or `f64::INFINITY` to block
+/// the room.
+///
+/// Returns `None` if no route could be found.
+pub fn find_route<F>(
+    from: RoomName,
+    to: RoomName,
+    shard_data: &OfflineShardData,
+    route_callback: F,
+) -> Option<Vec<RouteStep>>
+where
+    F: FnMut(RoomName, RoomName, RoomType, RoomStatus) -> f64,
+{
+    let exits =
+        |room_name: RoomName| {
+            let Some(room) = shard_data.rooms.get(&room_name) else {
+                return Vec::new();
+            };
+            describe_exits(room_name, &room.terrain)
+                .into_iter()
+                .filter_map(|(exit, neighbour_name)| {
+                    let neighbour = shard_data.rooms.get(&neighbour_name)?;
+                    // both sides of the border need to be open
+                    let neighbour_has_exit = describe_exits(neighbour_name, &neighbour.terrain)
+                        .get(&opposite_exit(exit))
+                        .is_some_and(|back| *back == room_name);
+                    (neighbour_has_exit && statuses_connect(room.status, neighbour.status))
+                        .then_some((exit, neighbour_name, neighbour.status))
+                })
+                .collect()
+        };
+    find_route_with(from, to, exits, route_callback)
+}
+
+/// Finds the cheapest route of rooms from one room to another, moving only
+/// through the provided set of rooms; the equivalent of the game's
+/// `Game.map.findRoute`.
+///
+/// Without terrain available, all rooms are assumed to have exits on every
+/// side and a status of [`RoomStatus::Normal`]. The route callback is called
+/// in the same way as for [`find_route`].
+///
+/// Returns `None` if no route could be found.
+pub fn find_route_in_rooms<F>(
+    from: RoomName,
+    to: RoomName,
+    rooms: &HashSet<RoomName>,
+    route_callback: F,
+) -> Option<Vec<RouteStep>>
+where
+    F: FnMut(RoomName, RoomName, RoomType, RoomStatus) -> f64,
+{
+    let exits = |room_name: RoomName| {
+        [
+            ExitDirection::Top,
+            ExitDirection::Right,
+            ExitDirection::Bottom,
+            ExitDirection::Left,
+        ]
+        .into_iter()
+        .filter_map(|exit| {
+            neighbour_room_name(room_name, exit)
+                .filter(|neighbour| rooms.contains(neighbour))
+                .map(|neighbour| (exit, neighbour, RoomStatus::Normal))
+        })
+        .collect()
+    };
+    find_route_with(from, to, exits, route_callback)
+}
+
+fn opposite_exit(exit: ExitDirection) -> ExitDirection {
+    match exit {
+        ExitDirection::Top => ExitDirection::Bottom,
+        ExitDirection::Right => ExitDirection::Left,
+        ExitDirection::Bottom => ExitDirection::Top,
+        ExitDirection::Left => ExitDirection::Right,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    use crate::offline_map::OfflineRoomData;
+
     #[test]
     fn room_types() {
         assert_eq!(
@@ -144,4 +496,208 @@ mod test {
             RoomType::Highway
         );
     }
+
+    fn make_room(name: &str, status: RoomStatus, bits: Box<[u8; 2500]>) -> OfflineRoomData {
+        OfflineRoomData {
+            room_name: RoomName::new(name).unwrap(),
+            status,
+            bus: false,
+            terrain: LocalRoomTerrain::new_from_bits(bits),
+            objects: Vec::new(),
+        }
+    }
+
+    fn make_shard(rooms: Vec<OfflineRoomData>) -> OfflineShardData {
+        OfflineShardData {
+            description: String::new(),
+            rooms: rooms
+                .into_iter()
+                .map(|room| (room.room_name, room))
+                .collect(),
+        }
+    }
+
+    fn default_cost(_: RoomName, _: RoomName, _: RoomType, _: RoomStatus) -> f64 {
+        1.
+    }
+
+    #[test]
+    fn exits_from_terrain() {
+        let mut bits = Box::new([0; 2500]);
+        // wall off the whole right side of the room
+        for y in 0..50 {
+            bits[y * 50 + 49] = 1;
+        }
+        let room_name = RoomName::new("W1N1").unwrap();
+        let exits = describe_exits(room_name, &LocalRoomTerrain::new_from_bits(bits));
+
+        assert_eq!(exits.len(), 3);
+        assert_eq!(exits[&ExitDirection::Top], RoomName::new("W1N2").unwrap());
+        assert_eq!(
+            exits[&ExitDirection::Bottom],
+            RoomName::new("W1N0").unwrap()
+        );
+        assert_eq!(exits[&ExitDirection::Left], RoomName::new("W2N1").unwrap());
+        assert!(!exits.contains_key(&ExitDirection::Right));
+    }
+
+    #[test]
+    fn route_in_rooms() {
+        let rooms: HashSet<RoomName> = ["W1N1", "W1N2", "W0N2", "E0N2", "E0N1"]
+            .into_iter()
+            .map(|name| RoomName::new(name).unwrap())
+            .collect();
+        let route = find_route_in_rooms(
+            RoomName::new("W1N1").unwrap(),
+            RoomName::new("E0N1").unwrap(),
+            &rooms,
+            default_cost,
+        )
+        .unwrap();
+
+        let names: Vec<_> = route.iter().map(|step| step.room.to_string()).collect();
+        assert_eq!(names, ["W1N2", "W0N2", "E0N2", "E0N1"]);
+        assert_eq!(route[0].exit, ExitDirection::Top);
+        assert_eq!(route[3].exit, ExitDirection::Bottom);
+    }
+
+    #[test]
+    fn route_callback_costs() {
+        let rooms: HashSet<RoomName> = ["W1N1", "W0N1", "W1N2", "W0N2"]
+            .into_iter()
+            .map(|name| RoomName::new(name).unwrap())
+            .collect();
+        let blocked = RoomName::new("W0N1").unwrap();
+        let route = find_route_in_rooms(
+            RoomName::new("W1N1").unwrap(),
+            RoomName::new("W0N2").unwrap(),
+            &rooms,
+            |room_name, _, _, _| {
+                if room_name == blocked {
+                    f64::INFINITY
+                } else {
+                    1.
+                }
+            },
+        )
+        .unwrap();
+        let names: Vec<_> = route.iter().map(|step| step.room.to_string()).collect();
+        assert_eq!(names, ["W1N2", "W0N2"]);
+
+        assert!(find_route_in_rooms(
+            RoomName::new("W1N1").unwrap(),
+            RoomName::new("W0N1").unwrap(),
+            &rooms,
+            |room_name, _, _, _| if room_name == blocked {
+                f64::INFINITY
+            } else {
+                1.
+            },
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn route_through_shard_data() {
+        let mut walled_bottom = Box::new([0; 2500]);
+        for x in 0..50 {
+            walled_bottom[49 * 50 + x] = 1;
+        }
+        let shard = make_shard(vec![
+            make_room("W1N2", RoomStatus::Normal, walled_bottom),
+            make_room("W1N1", RoomStatus::Normal, Box::new([0; 2500])),
+            make_room("W2N1", RoomStatus::Novice, Box::new([0; 2500])),
+            make_room("W2N2", RoomStatus::Normal, Box::new([0; 2500])),
+            make_room("W3N1", RoomStatus::Normal, Box::new([0; 2500])),
+            make_room("W3N2", RoomStatus::Normal, Box::new([0; 2500])),
+        ]);
+
+        // the direct exit is walled off, so the route goes around through
+        // the rooms to the west, but can't cut through the novice room
+        let route = find_route(
+            RoomName::new("W1N1").unwrap(),
+            RoomName::new("W1N2").unwrap(),
+            &shard,
+            default_cost,
+        );
+        assert!(route.is_none());
+
+        let route = find_route(
+            RoomName::new("W3N1").unwrap(),
+            RoomName::new("W1N2").unwrap(),
+            &shard,
+            |_, _, room_type, status| {
+                assert_eq!(room_type, RoomType::Normal);
+                assert_eq!(status, RoomStatus::Normal);
+                1.
+            },
+        )
+        .unwrap();
+        let names: Vec<_> = route.iter().map(|step| step.room.to_string()).collect();
+        assert_eq!(names, ["W3N2", "W2N2", "W1N2"]);
+    }
+
+    #[test]
+    fn exit_segments_and_groups() {
+        let mut bits = Box::new([0; 2500]);
+        // wall off the right side entirely, and split the top side into two
+        // segments at x = 20
+        for y in 0..50 {
+            bits[y * 50 + 49] = 1;
+        }
+        bits[20] = 1;
+        // a wall across the room at y = 25, splitting the bottom exit off from
+        // the rest
+        for x in 0..50 {
+            bits[25 * 50 + x] = 1;
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+
+        assert_eq!(exit_positions(&terrain, ExitDirection::Top).len(), 48);
+        assert!(exit_positions(&terrain, ExitDirection::Right).is_empty());
+        assert_eq!(exit_positions(&terrain, ExitDirection::Left).len(), 49);
+
+        let segments = exit_segments(&terrain);
+        let summary: Vec<_> = segments
+            .iter()
+            .map(|segment| (segment.exit, segment.positions.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (ExitDirection::Top, 20),
+                (ExitDirection::Top, 28),
+                (ExitDirection::Bottom, 49),
+                (ExitDirection::Left, 25),
+                (ExitDirection::Left, 24),
+            ]
+        );
+        assert_eq!(
+            segments[2].destination(RoomName::new("W1N1").unwrap()),
+            Some(RoomName::new("W1N0").unwrap())
+        );
+
+        let groups = exit_groups(&terrain);
+        assert_eq!(groups.len(), 2);
+        let group_summary: Vec<Vec<_>> = groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|segment| (segment.exit, segment.positions.len()))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            group_summary,
+            [
+                vec![
+                    (ExitDirection::Top, 20),
+                    (ExitDirection::Top, 28),
+                    (ExitDirection::Left, 25)
+                ],
+                vec![(ExitDirection::Bottom, 49), (ExitDirection::Left, 24)],
+            ]
+        );
+    }
 }
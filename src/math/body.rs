@@ -0,0 +1,389 @@
+use screeps::constants::*;
+
+/// A single body part of a creep, along with the compound it's boosted with,
+/// if any.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BoostedPart {
+    pub part: Part,
+    pub boost: Option<ResourceType>,
+}
+
+impl BoostedPart {
+    /// Creates an unboosted body part.
+    pub fn new(part: Part) -> Self {
+        BoostedPart { part, boost: None }
+    }
+
+    /// Creates a body part boosted with the given compound.
+    pub fn boosted(part: Part, boost: ResourceType) -> Self {
+        BoostedPart {
+            part,
+            boost: Some(boost),
+        }
+    }
+
+    /// The multiplier the part's boost applies to its main effect, such as
+    /// carry capacity for `CARRY` parts or fatigue reduction for `MOVE` parts,
+    /// or 1 if it's unboosted or boosted with a compound that doesn't affect
+    /// the part.
+    ///
+    /// `TOUGH` boosts reduce damage rather than multiplying anything, and
+    /// `WORK` boosts depend on the action, so these always have a multiplier
+    /// of 1 here.
+    pub fn boost_multiplier(&self) -> u32 {
+        match (self.part, self.boost.and_then(|boost| boost.boost())) {
+            (Part::Attack, Some(Boost::Attack(multiplier)))
+            | (Part::RangedAttack, Some(Boost::RangedAttack(multiplier)))
+            | (Part::Heal, Some(Boost::Heal(multiplier)))
+            | (Part::Carry, Some(Boost::Carry(multiplier)))
+            | (Part::Move, Some(Boost::Move(multiplier))) => multiplier,
+            _ => 1,
+        }
+    }
+}
+
+impl From<Part> for BoostedPart {
+    fn from(part: Part) -> Self {
+        BoostedPart::new(part)
+    }
+}
+
+/// The kinds of position a creep can move onto, which each multiply the
+/// fatigue generated by moving differently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MovementTerrain {
+    Road,
+    Plain,
+    Swamp,
+}
+
+impl MovementTerrain {
+    /// The fatigue generated by each weighted body part when moving onto this
+    /// kind of position.
+    pub fn fatigue_per_part(self) -> u32 {
+        match self {
+            MovementTerrain::Road => 1,
+            MovementTerrain::Plain => 2,
+            MovementTerrain::Swamp => 10,
+        }
+    }
+}
+
+/// Provides the energy cost of spawning a body; boosts are applied after
+/// spawning, so don't affect the cost.
+pub fn body_cost(body: &[BoostedPart]) -> u32 {
+    body.iter().map(|part| part.part.cost()).sum()
+}
+
+/// Provides the number of ticks it takes to spawn a body, without any power
+/// effects on the spawn.
+pub fn body_spawn_time(body: &[BoostedPart]) -> u32 {
+    body.len() as u32 * CREEP_SPAWN_TIME
+}
+
+/// Provides the total carry capacity of a body, including boosts.
+pub fn body_carry_capacity(body: &[BoostedPart]) -> u32 {
+    body.iter()
+        .filter(|part| part.part == Part::Carry)
+        .map(|part| CARRY_CAPACITY * part.boost_multiplier())
+        .sum()
+}
+
+/// Provides the fatigue a body generates when moving onto the given terrain
+/// while carrying the given amount of resources.
+///
+/// Every part other than `MOVE` generates fatigue, except for `CARRY` parts
+/// which are empty; as in the game, resources fill `CARRY` parts starting from
+/// the last part of the body.
+pub fn body_fatigue(body: &[BoostedPart], carried: u32, terrain: MovementTerrain) -> u32 {
+    let mut remaining = carried;
+    let weighted_parts = body
+        .iter()
+        .rev()
+        .filter(|part| match part.part {
+            Part::Move => false,
+            Part::Carry => {
+                let used = remaining > 0;
+                remaining = remaining.saturating_sub(CARRY_CAPACITY * part.boost_multiplier());
+                used
+            }
+            _ => true,
+        })
+        .count() as u32;
+    weighted_parts * terrain.fatigue_per_part()
+}
+
+/// Provides the amount of fatigue a body recovers each tick from its `MOVE`
+/// parts, including boosts.
+pub fn body_move_power(body: &[BoostedPart]) -> u32 {
+    body.iter()
+        .filter(|part| part.part == Part::Move)
+        .map(|part| 2 * part.boost_multiplier())
+        .sum()
+}
+
+/// Provides the number of ticks a body takes to move each tile over the given
+/// terrain while carrying the given amount of resources, or `None` if it has
+/// no `MOVE` parts to move with.
+pub fn body_ticks_per_tile(
+    body: &[BoostedPart],
+    carried: u32,
+    terrain: MovementTerrain,
+) -> Option<u32> {
+    let fatigue = body_fatigue(body, carried, terrain);
+    let move_power = body_move_power(body);
+    if move_power == 0 {
+        return None;
+    }
+    Some(fatigue.div_ceil(move_power).max(1))
+}
+
+/// The movement speed a generated body needs to reach, as used by
+/// [`generate_body`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MovementGoal {
+    /// The terrain the body should be able to move over
+    pub terrain: MovementTerrain,
+    /// Whether the body's `CARRY` parts should be treated as full
+    pub loaded: bool,
+    /// The number of ticks the body may take per tile; 0 is treated as 1
+    pub ticks_per_tile: u32,
+}
+
+impl Default for MovementGoal {
+    fn default() -> Self {
+        MovementGoal {
+            terrain: MovementTerrain::Road,
+            loaded: true,
+            ticks_per_tile: 1,
+        }
+    }
+}
+
+/// Generates the largest body made up of repetitions of the pattern, plus
+/// enough `MOVE` parts to meet the movement goal, that can be spawned with the
+/// energy budget and fits within [`MAX_CREEP_SIZE`]. Returns `None` if not even
+/// a single repetition fits.
+///
+/// The pattern is repeated at most `max_repeats` times. Any `MOVE` parts in the
+/// pattern count towards the movement goal, and the generated bodies are
+/// unboosted. Bodies always have at least one `MOVE` part, even when none of
+/// the other parts generate fatigue.
+///
+/// The parts are ordered with all `TOUGH` parts first and all `HEAL` parts
+/// last, so that they're the first and last parts to take damage; the other
+/// parts of the pattern are repeated in order between them, with `MOVE` parts
+/// spread evenly among them.
+pub fn generate_body(
+    energy_budget: u32,
+    pattern: &[Part],
+    max_repeats: u32,
+    movement: MovementGoal,
+) -> Option<Vec<Part>> {
+    let count = |part: Part| pattern.iter().filter(|p| **p == part).count() as u32;
+    let pattern_moves = count(Part::Move);
+    let weighted_parts = pattern
+        .iter()
+        .filter(|part| match part {
+            Part::Move => false,
+            Part::Carry => movement.loaded,
+            _ => true,
+        })
+        .count() as u32;
+    let pattern_cost: u32 = pattern.iter().map(|part| part.cost()).sum();
+    let move_power = 2 * movement.ticks_per_tile.max(1);
+
+    // A body always needs at least one move part to be able to move, even
+    // if none of its other parts generate fatigue
+    let extra_moves = |repeats: u32| {
+        (repeats * weighted_parts * movement.terrain.fatigue_per_part())
+            .div_ceil(move_power)
+            .max(1)
+            .saturating_sub(repeats * pattern_moves)
+    };
+    if pattern.is_empty() {
+        return None;
+    }
+    let max_repeats = max_repeats.min(MAX_CREEP_SIZE / pattern.len() as u32);
+    let repeats = (1..=max_repeats).rev().find(|repeats| {
+        let moves = extra_moves(*repeats);
+        repeats * pattern.len() as u32 + moves <= MAX_CREEP_SIZE
+            && repeats * pattern_cost + moves * Part::Move.cost() <= energy_budget
+    })?;
+
+    let repeated = || (0..repeats).flat_map(|_| pattern.iter().copied());
+    let middle: Vec<Part> = repeated()
+        .filter(|part| !matches!(part, Part::Tough | Part::Heal | Part::Move))
+        .collect();
+    let moves = (repeats * pattern_moves + extra_moves(repeats)) as usize;
+
+    let mut body: Vec<Part> = repeated().filter(|part| *part == Part::Tough).collect();
+    let mut moves_placed = 0;
+    for (idx, part) in middle.iter().enumerate() {
+        body.push(*part);
+        // place moves so that they're evenly spread after each part
+        while moves_placed < moves * (idx + 1) / middle.len() {
+            body.push(Part::Move);
+            moves_placed += 1;
+        }
+    }
+    body.extend(std::iter::repeat_n(Part::Move, moves - moves_placed));
+    body.extend(repeated().filter(|part| *part == Part::Heal));
+    Some(body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn body(parts: &[Part]) -> Vec<BoostedPart> {
+        parts.iter().map(|part| BoostedPart::new(*part)).collect()
+    }
+
+    #[test]
+    fn body_cost_and_spawn_time() {
+        let worker = body(&[Part::Work, Part::Carry, Part::Move]);
+        assert_eq!(body_cost(&worker), 200);
+        assert_eq!(body_spawn_time(&worker), 9);
+        assert_eq!(body_carry_capacity(&worker), 50);
+
+        let boosted_hauler = [
+            BoostedPart::boosted(Part::Carry, ResourceType::CatalyzedKeaniumAcid),
+            BoostedPart::new(Part::Carry),
+            BoostedPart::boosted(Part::Move, ResourceType::CatalyzedKeaniumAcid),
+        ];
+        assert_eq!(body_cost(&boosted_hauler), 150);
+        assert_eq!(body_carry_capacity(&boosted_hauler), 250);
+        // the move part's boost doesn't affect it
+        assert_eq!(body_move_power(&boosted_hauler), 2);
+    }
+
+    #[test]
+    fn worker_movement() {
+        let worker = body(&[Part::Work, Part::Carry, Part::Move]);
+        assert_eq!(body_fatigue(&worker, 0, MovementTerrain::Plain), 2);
+        assert_eq!(body_fatigue(&worker, 50, MovementTerrain::Plain), 4);
+        assert_eq!(
+            body_ticks_per_tile(&worker, 0, MovementTerrain::Plain),
+            Some(1)
+        );
+        assert_eq!(
+            body_ticks_per_tile(&worker, 1, MovementTerrain::Plain),
+            Some(2)
+        );
+        assert_eq!(
+            body_ticks_per_tile(&worker, 50, MovementTerrain::Road),
+            Some(1)
+        );
+        assert_eq!(
+            body_ticks_per_tile(&worker, 0, MovementTerrain::Swamp),
+            Some(5)
+        );
+        assert_eq!(
+            body_ticks_per_tile(&worker, 50, MovementTerrain::Swamp),
+            Some(10)
+        );
+
+        assert_eq!(
+            body_ticks_per_tile(&body(&[Part::Work]), 0, MovementTerrain::Road),
+            None
+        );
+        assert_eq!(
+            body_ticks_per_tile(&body(&[Part::Move]), 0, MovementTerrain::Swamp),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn boosted_movement() {
+        let mut hauler = body(&[Part::Carry, Part::Carry, Part::Carry, Part::Carry]);
+        hauler.push(BoostedPart::boosted(
+            Part::Move,
+            ResourceType::CatalyzedZynthiumAlkalide,
+        ));
+        assert_eq!(body_move_power(&hauler), 8);
+        // only the last carry part is used
+        assert_eq!(body_fatigue(&hauler, 50, MovementTerrain::Swamp), 10);
+        assert_eq!(
+            body_ticks_per_tile(&hauler, 50, MovementTerrain::Swamp),
+            Some(2)
+        );
+        assert_eq!(
+            body_ticks_per_tile(&hauler, 200, MovementTerrain::Plain),
+            Some(1)
+        );
+
+        // the unboosted carry part at the back is filled before the boosted
+        // one at the front
+        let hauler = [
+            BoostedPart::boosted(Part::Carry, ResourceType::CatalyzedKeaniumAcid),
+            BoostedPart::new(Part::Carry),
+            BoostedPart::new(Part::Move),
+        ];
+        assert_eq!(body_fatigue(&hauler, 50, MovementTerrain::Plain), 2);
+        assert_eq!(body_fatigue(&hauler, 100, MovementTerrain::Plain), 4);
+    }
+
+    #[test]
+    fn generate_worker_body() {
+        use Part::*;
+
+        let body = generate_body(550, &[Work, Carry], 50, MovementGoal::default()).unwrap();
+        assert_eq!(body, [Work, Carry, Move, Work, Carry, Move]);
+
+        // moving on plains needs twice as many moves
+        let goal = MovementGoal {
+            terrain: MovementTerrain::Plain,
+            ..Default::default()
+        };
+        let body = generate_body(550, &[Work, Carry], 50, goal).unwrap();
+        assert_eq!(body, [Work, Move, Carry, Move, Work, Move, Carry, Move]);
+
+        // limited by the number of repeats
+        let body = generate_body(10000, &[Work], 5, MovementGoal::default()).unwrap();
+        assert_eq!(body.iter().filter(|part| **part == Work).count(), 5);
+        assert_eq!(body.iter().filter(|part| **part == Move).count(), 3);
+
+        // empty carry parts don't generate fatigue, but the body still needs
+        // a move part
+        let unloaded = MovementGoal {
+            loaded: false,
+            ..Default::default()
+        };
+        let body = generate_body(500, &[Carry], 50, unloaded).unwrap();
+        assert_eq!(
+            body,
+            [Carry, Carry, Carry, Carry, Carry, Carry, Carry, Carry, Carry, Move]
+        );
+        let body = generate_body(100, &[Carry], 50, unloaded).unwrap();
+        assert_eq!(body, [Carry, Move]);
+        assert_eq!(generate_body(50, &[Carry], 50, unloaded), None);
+
+        assert_eq!(generate_body(100, &[Work, Carry], 50, goal), None);
+        assert_eq!(generate_body(1000, &[], 50, goal), None);
+    }
+
+    #[test]
+    fn generate_combat_body() {
+        use Part::*;
+
+        let goal = MovementGoal {
+            terrain: MovementTerrain::Plain,
+            loaded: false,
+            ticks_per_tile: 1,
+        };
+        let body = generate_body(12900, &[Tough, RangedAttack, Heal], 50, goal).unwrap();
+        assert_eq!(body.len(), 48);
+        assert!(body.len() as u32 <= MAX_CREEP_SIZE);
+        assert!(body[..8].iter().all(|part| *part == Tough));
+        assert!(body[40..].iter().all(|part| *part == Heal));
+        assert!(body[8..40].contains(&RangedAttack));
+
+        let boosted: Vec<_> = body.iter().map(|part| BoostedPart::new(*part)).collect();
+        assert_eq!(body_cost(&boosted), 8 * 560);
+        assert_eq!(
+            body_ticks_per_tile(&boosted, 0, MovementTerrain::Plain),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use screeps::{constants::StructureType, local::RoomXY};
+
+/// The highest level a room controller can reach.
+const MAX_RCL: u32 = 8;
+
+/// A list of planned structures and their positions, in priority order.
+pub type PlannedStructures = Vec<(RoomXY, StructureType)>;
+
+/// The planned structures which change at a single controller level, as
+/// calculated by [`build_schedule`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RclBuildStage {
+    /// The controller level of this stage
+    pub rcl: u32,
+    /// Planned structures which first become available to build at this level
+    pub newly_available: PlannedStructures,
+    /// Planned structures which can't be built at this level, as the limit
+    /// for their type has already been reached
+    pub over_limit: PlannedStructures,
+}
+
+/// Splits a list of planned structures into those which can be built at the
+/// given controller level and those which are over the limit for their type,
+/// following the game's `CONTROLLER_STRUCTURES` constant.
+///
+/// Structures are listed in priority order, so when a type is over the limit,
+/// the earliest planned structures of that type are the ones which can be
+/// built.
+pub fn partition_by_rcl_limits(
+    planned: &[(RoomXY, StructureType)],
+    rcl: u32,
+) -> (PlannedStructures, PlannedStructures) {
+    let mut counts: HashMap<StructureType, u32> = HashMap::new();
+    planned.iter().partition(|(_, structure_type)| {
+        let count = counts.entry(*structure_type).or_default();
+        *count += 1;
+        *count <= structure_type.controller_structures(rcl)
+    })
+}
+
+/// Provides the build schedule for a list of planned structures across each
+/// controller level from 1 to 8, in priority order.
+///
+/// Each stage lists the structures which become available at that level and
+/// those which still can't be built there; any structures which are over the
+/// limit at level 8 can never be built, and indicate a problem with the plan.
+pub fn build_schedule(planned: &[(RoomXY, StructureType)]) -> Vec<RclBuildStage> {
+    let mut previous_over_limit = planned.to_vec();
+    (1..=MAX_RCL)
+        .map(|rcl| {
+            let (_, over_limit) = partition_by_rcl_limits(planned, rcl);
+            let newly_available = previous_over_limit
+                .iter()
+                .filter(|structure| !over_limit.contains(structure))
+                .copied()
+                .collect();
+            previous_over_limit.clone_from(&over_limit);
+            RclBuildStage {
+                rcl,
+                newly_available,
+                over_limit,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use StructureType::*;
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    #[test]
+    fn partition_keeps_earliest_structures() {
+        let planned = [
+            (make_xy(10, 10), Spawn),
+            (make_xy(11, 10), Tower),
+            (make_xy(12, 10), Spawn),
+            (make_xy(13, 10), Tower),
+        ];
+        let (available, over_limit) = partition_by_rcl_limits(&planned, 3);
+        assert_eq!(available, [planned[0], planned[1]]);
+        assert_eq!(over_limit, [planned[2], planned[3]]);
+
+        let (available, over_limit) = partition_by_rcl_limits(&planned, 8);
+        assert_eq!(available, planned);
+        assert!(over_limit.is_empty());
+    }
+
+    #[test]
+    fn schedule_for_extensions() {
+        let planned: Vec<_> = (0..65)
+            .map(|i| (make_xy(i % 40 + 5, i / 40 + 5), Extension))
+            .collect();
+        let schedule = build_schedule(&planned);
+        assert_eq!(schedule.len(), 8);
+
+        let new_counts: Vec<_> = schedule
+            .iter()
+            .map(|stage| stage.newly_available.len())
+            .collect();
+        assert_eq!(new_counts, [0, 5, 5, 10, 10, 10, 10, 10]);
+        assert_eq!(schedule[1].newly_available, planned[0..5]);
+        assert_eq!(schedule[0].over_limit.len(), 65);
+        // 5 extensions more than can ever be built
+        assert_eq!(schedule[7].over_limit, planned[60..]);
+    }
+}
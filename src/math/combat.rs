@@ -0,0 +1,258 @@
+use screeps::constants::*;
+
+use crate::math::BoostedPart;
+
+/// The hits of each body part of a creep, translating the game's
+/// `BODYPART_HITS` constant.
+pub const BODYPART_HITS: u32 = 100;
+
+fn part_power(body: &[BoostedPart], part: Part, power: u32) -> u32 {
+    body.iter()
+        .filter(|body_part| body_part.part == part)
+        .map(|body_part| power * body_part.boost_multiplier())
+        .sum()
+}
+
+/// Provides the damage done each tick by a body's melee attacks, including
+/// boosts.
+pub fn body_attack_power(body: &[BoostedPart]) -> u32 {
+    part_power(body, Part::Attack, ATTACK_POWER)
+}
+
+/// Provides the damage done each tick by a body's ranged attacks, including
+/// boosts.
+pub fn body_ranged_attack_power(body: &[BoostedPart]) -> u32 {
+    part_power(body, Part::RangedAttack, RANGED_ATTACK_POWER)
+}
+
+/// Provides the damage done each tick by a body's ranged mass attacks to a
+/// target at the given range, including boosts; targets further than range 3
+/// take no damage.
+pub fn body_ranged_mass_attack_power(body: &[BoostedPart], range: u8) -> u32 {
+    let power = match range {
+        0 | 1 => RANGED_MASS_ATTACK_POWER_RANGE_1,
+        2 => RANGED_MASS_ATTACK_POWER_RANGE_2,
+        3 => RANGED_MASS_ATTACK_POWER_RANGE_3,
+        _ => 0,
+    };
+    part_power(body, Part::RangedAttack, power)
+}
+
+/// Provides the damage healed each tick by a body's adjacent heals, including
+/// boosts.
+pub fn body_heal_power(body: &[BoostedPart]) -> u32 {
+    part_power(body, Part::Heal, HEAL_POWER)
+}
+
+/// Provides the damage healed each tick by a body's ranged heals, including
+/// boosts.
+pub fn body_ranged_heal_power(body: &[BoostedPart]) -> u32 {
+    part_power(body, Part::Heal, RANGED_HEAL_POWER)
+}
+
+/// Provides the ratio of incoming damage taken by a body part, which is below
+/// 1 for `TOUGH` parts boosted to reduce damage, and 1 for all other parts.
+pub fn part_damage_ratio(part: &BoostedPart) -> f64 {
+    match (part.part, part.boost.and_then(|boost| boost.boost())) {
+        (Part::Tough, Some(Boost::Tough(ratio))) => ratio as f64,
+        _ => 1.,
+    }
+}
+
+/// Provides the total amount of incoming damage a body can take before dying,
+/// with every part at full hits; boosted `TOUGH` parts take less damage, so
+/// add more than their hits to the total.
+///
+/// This ignores the rounding applied by the game to each instance of damage,
+/// so may differ slightly from the damage needed in practice; see
+/// [`apply_damage`] for simulating individual instances of damage exactly.
+pub fn body_effective_hits(body: &[BoostedPart]) -> u32 {
+    body.iter()
+        .map(|part| BODYPART_HITS as f64 / part_damage_ratio(part))
+        .sum::<f64>()
+        .round() as u32
+}
+
+/// A body part of a creep along with its current hits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BodyPartState {
+    pub part: BoostedPart,
+    pub hits: u32,
+}
+
+impl BodyPartState {
+    /// Creates a body part state with full hits.
+    pub fn full(part: BoostedPart) -> Self {
+        BodyPartState {
+            part,
+            hits: BODYPART_HITS,
+        }
+    }
+}
+
+/// The result of applying damage to a creep, as calculated by
+/// [`apply_damage`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DamageResult {
+    /// The state of each body part after the damage, in body order
+    pub body: Vec<BodyPartState>,
+    /// The damage actually taken after reduction from boosted `TOUGH` parts
+    pub damage_taken: u32,
+    /// The parts which went from having hits to having none
+    pub parts_lost: Vec<BoostedPart>,
+    /// Whether the creep was killed
+    pub dead: bool,
+}
+
+/// Applies an instance of incoming damage to a creep's body in the same way as
+/// the game engine, returning the resulting state of the body.
+///
+/// Boosted `TOUGH` parts reduce the damage taken by the portion of the damage
+/// they absorb, going through the body in order; the reduction is rounded, and
+/// the remaining damage comes off the creep's total hits. The total hits are
+/// then spread over the body from the last part backwards, so damage always
+/// destroys parts from the front of the body first.
+///
+/// Damage from several sources in the same tick, such as multiple towers, is
+/// combined by the game and should be applied as a single instance.
+pub fn apply_damage(body: &[BodyPartState], damage: u32) -> DamageResult {
+    let mut damage_reduce = 0.;
+    let mut damage_effective = damage as f64;
+    for part in body {
+        if damage_effective <= 0. {
+            break;
+        }
+        let damage_ratio = part_damage_ratio(&part.part);
+        let absorbed = (part.hits as f64 / damage_ratio).min(damage_effective);
+        damage_reduce += absorbed * (1. - damage_ratio);
+        damage_effective -= absorbed;
+    }
+    let damage_taken = damage.saturating_sub(damage_reduce.round() as u32);
+
+    let total_hits: u32 = body.iter().map(|part| part.hits).sum();
+    let mut remaining_hits = total_hits.saturating_sub(damage_taken);
+    let mut new_body = body.to_vec();
+    for part in new_body.iter_mut().rev() {
+        part.hits = remaining_hits.min(BODYPART_HITS);
+        remaining_hits -= part.hits;
+    }
+
+    let parts_lost = body
+        .iter()
+        .zip(&new_body)
+        .filter(|(before, after)| before.hits > 0 && after.hits == 0)
+        .map(|(before, _)| before.part)
+        .collect();
+
+    DamageResult {
+        dead: new_body.iter().all(|part| part.hits == 0),
+        body: new_body,
+        damage_taken,
+        parts_lost,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use ResourceType::*;
+
+    fn body(parts: &[(Part, Option<ResourceType>)]) -> Vec<BoostedPart> {
+        parts
+            .iter()
+            .map(|(part, boost)| BoostedPart {
+                part: *part,
+                boost: *boost,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn combat_powers() {
+        let attacker = body(&[
+            (Part::Attack, None),
+            (Part::Attack, Some(CatalyzedUtriumAcid)),
+            (Part::RangedAttack, Some(KeaniumOxide)),
+            (Part::Heal, Some(LemergiumAlkalide)),
+            (Part::Heal, None),
+            // boosts for the wrong part have no effect
+            (Part::Move, Some(CatalyzedUtriumAcid)),
+        ]);
+        assert_eq!(body_attack_power(&attacker), 30 + 120);
+        assert_eq!(body_ranged_attack_power(&attacker), 20);
+        assert_eq!(body_ranged_mass_attack_power(&attacker, 1), 20);
+        assert_eq!(body_ranged_mass_attack_power(&attacker, 2), 8);
+        assert_eq!(body_ranged_mass_attack_power(&attacker, 3), 2);
+        assert_eq!(body_ranged_mass_attack_power(&attacker, 4), 0);
+        assert_eq!(body_heal_power(&attacker), 36 + 12);
+        assert_eq!(body_ranged_heal_power(&attacker), 12 + 4);
+    }
+
+    #[test]
+    fn effective_hits_with_tough() {
+        let unboosted = body(&[(Part::Tough, None), (Part::Move, None)]);
+        assert_eq!(body_effective_hits(&unboosted), 200);
+
+        let boosted = body(&[
+            (Part::Tough, Some(CatalyzedGhodiumAlkalide)),
+            (Part::Tough, Some(GhodiumOxide)),
+            (Part::Move, None),
+        ]);
+        assert_eq!(body_effective_hits(&boosted), 333 + 143 + 100);
+    }
+
+    fn full_body(parts: &[(Part, Option<ResourceType>)]) -> Vec<BodyPartState> {
+        body(parts).into_iter().map(BodyPartState::full).collect()
+    }
+
+    #[test]
+    fn apply_unboosted_damage() {
+        let creep = full_body(&[
+            (Part::Tough, None),
+            (Part::Attack, None),
+            (Part::Move, None),
+        ]);
+        let result = apply_damage(&creep, 150);
+        assert_eq!(result.damage_taken, 150);
+        let hits: Vec<_> = result.body.iter().map(|part| part.hits).collect();
+        assert_eq!(hits, [0, 50, 100]);
+        assert_eq!(result.parts_lost, [BoostedPart::new(Part::Tough)]);
+        assert!(!result.dead);
+
+        // damage continues from the current state
+        let result = apply_damage(&result.body, 160);
+        assert_eq!(
+            result.parts_lost,
+            [BoostedPart::new(Part::Attack), BoostedPart::new(Part::Move)]
+        );
+        assert!(result.dead);
+    }
+
+    #[test]
+    fn apply_damage_through_boosted_tough() {
+        let creep = full_body(&[
+            (Part::Tough, Some(CatalyzedGhodiumAlkalide)),
+            (Part::Move, None),
+            (Part::Move, None),
+        ]);
+
+        // fully absorbed by the tough part
+        let result = apply_damage(&creep, 300);
+        assert_eq!(result.damage_taken, 90);
+        assert_eq!(result.body[0].hits, 10);
+        assert!(result.parts_lost.is_empty());
+
+        // only the first 333.3 damage is reduced
+        let result = apply_damage(&creep, 400);
+        assert_eq!(result.damage_taken, 167);
+        let hits: Vec<_> = result.body.iter().map(|part| part.hits).collect();
+        assert_eq!(hits, [0, 33, 100]);
+
+        // tough parts behind others still reduce damage, as the game
+        // considers the whole body
+        let creep = full_body(&[(Part::Move, None), (Part::Tough, Some(GhodiumOxide))]);
+        let result = apply_damage(&creep, 150);
+        assert_eq!(result.damage_taken, 135);
+    }
+}
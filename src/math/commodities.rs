@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use screeps::constants::*;
+
+/// A single factory production needed as part of a [`CommodityPlan`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommodityStep {
+    /// The commodity produced
+    pub product: ResourceType,
+    /// The total amount of each component consumed
+    pub components: HashMap<ResourceType, u32>,
+    /// The total amount of the product made
+    pub amount: u32,
+    /// The number of times the factory needs to produce the commodity
+    pub runs: u32,
+    /// The total cooldown of the factory over all the runs
+    pub cooldown: u32,
+    /// The factory level needed to produce the commodity, if restricted
+    pub level: Option<u32>,
+}
+
+/// The productions and raw resources needed to make an amount of a commodity,
+/// as calculated by [`plan_commodity`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommodityPlan {
+    /// Every production needed, ordered so that each production's components
+    /// are made by earlier steps
+    pub steps: Vec<CommodityStep>,
+    /// The amount of each resource needed which isn't produced in a factory,
+    /// such as energy, minerals and deposit resources
+    pub raw_resources: HashMap<ResourceType, u32>,
+    /// The total cooldown of a single factory producing every step in turn
+    pub total_cooldown: u32,
+    /// The levels of any other factories needed for steps that the planned
+    /// factory level can't produce, in ascending order
+    pub other_levels: Vec<u32>,
+}
+
+// Whether a resource is used as-is rather than made in a factory; minerals,
+// ghodium and energy have recipes to decompress them from bars and batteries,
+// but are mined or made in labs for the purpose of making commodities
+fn is_raw(resource: ResourceType) -> bool {
+    use ResourceType::*;
+    resource.commodity_recipe().is_none()
+        || resource.reaction_components().is_some()
+        || matches!(
+            resource,
+            Energy | Hydrogen | Oxygen | Utrium | Lemergium | Keanium | Zynthium | Catalyst
+        )
+}
+
+// Adds the commodity and everything it's made from to `order`, with
+// components always before the commodities made from them
+fn visit(resource: ResourceType, order: &mut Vec<ResourceType>) {
+    if order.contains(&resource) {
+        return;
+    }
+    if !is_raw(resource) {
+        if let Some(recipe) = resource.commodity_recipe() {
+            let mut components: Vec<_> = recipe.components.into_keys().collect();
+            // the recipe's components are unordered, so sort them to keep the
+            // step order stable
+            components.sort_by_key(|component| {
+                RESOURCES_ALL
+                    .iter()
+                    .position(|resource| resource == component)
+            });
+            for component in components {
+                visit(component, order);
+            }
+        }
+    }
+    order.push(resource);
+}
+
+/// Expands the `COMMODITIES` constant into every factory production needed to
+/// make the given amount of a commodity, along with the raw resources they
+/// consume and the factory cooldown they take.
+///
+/// Each production makes the recipe's amount of its product, so amounts are
+/// rounded up to a multiple of it at each step. Energy, minerals and ghodium
+/// are treated as raw resources, rather than decompressed from batteries and
+/// bars. Steps which need a factory level other than `factory_level` are
+/// listed in the plan's `other_levels`; pass `None` for a factory without a
+/// level, which can only produce unrestricted commodities.
+pub fn plan_commodity(
+    target: ResourceType,
+    amount: u32,
+    factory_level: Option<u32>,
+) -> CommodityPlan {
+    let mut order = Vec::new();
+    visit(target, &mut order);
+
+    // Commodities always come later in the order than their components, so
+    // walking it backwards visits each resource after everything using it
+    let mut demand: HashMap<ResourceType, u32> = HashMap::from([(target, amount)]);
+    let mut steps = Vec::new();
+    let mut raw_resources = HashMap::new();
+    for resource in order.iter().rev() {
+        let needed = demand.get(resource).copied().unwrap_or(0);
+        if needed == 0 {
+            continue;
+        }
+        match resource.commodity_recipe().filter(|_| !is_raw(*resource)) {
+            Some(recipe) => {
+                let runs = needed.div_ceil(recipe.amount);
+                let components: HashMap<_, _> = recipe
+                    .components
+                    .into_iter()
+                    .map(|(component, amount)| (component, amount * runs))
+                    .collect();
+                for (component, amount) in &components {
+                    *demand.entry(*component).or_default() += amount;
+                }
+                steps.push(CommodityStep {
+                    product: *resource,
+                    components,
+                    amount: runs * recipe.amount,
+                    runs,
+                    cooldown: runs * recipe.cooldown,
+                    level: recipe.level,
+                });
+            }
+            None => {
+                raw_resources.insert(*resource, needed);
+            }
+        }
+    }
+    steps.reverse();
+
+    let mut other_levels: Vec<u32> = steps
+        .iter()
+        .filter_map(|step| step.level)
+        .filter(|level| Some(*level) != factory_level)
+        .collect();
+    other_levels.sort_unstable();
+    other_levels.dedup();
+
+    CommodityPlan {
+        total_cooldown: steps.iter().map(|step| step.cooldown).sum(),
+        steps,
+        raw_resources,
+        other_levels,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use ResourceType::*;
+
+    #[test]
+    fn plan_bar() {
+        let plan = plan_commodity(UtriumBar, 150, None);
+        assert_eq!(
+            plan.steps,
+            [CommodityStep {
+                product: UtriumBar,
+                components: HashMap::from([(Utrium, 1000), (Energy, 400)]),
+                amount: 200,
+                runs: 2,
+                cooldown: 40,
+                level: None,
+            }]
+        );
+        assert_eq!(
+            plan.raw_resources,
+            HashMap::from([(Utrium, 1000), (Energy, 400)])
+        );
+        assert_eq!(plan.total_cooldown, 40);
+        assert!(plan.other_levels.is_empty());
+
+        // raw resources aren't decompressed from bars
+        let plan = plan_commodity(Utrium, 500, None);
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.raw_resources, HashMap::from([(Utrium, 500)]));
+    }
+
+    #[test]
+    fn plan_leveled_commodity() {
+        let plan = plan_commodity(Fixtures, 1, Some(2));
+        let products: Vec<_> = plan.steps.iter().map(|step| step.product).collect();
+        assert_eq!(products.last(), Some(&Fixtures));
+        // each step's components are made by earlier steps or are raw
+        for (idx, step) in plan.steps.iter().enumerate() {
+            for component in step.components.keys() {
+                assert!(
+                    products[..idx].contains(component)
+                        || plan.raw_resources.contains_key(component)
+                );
+            }
+        }
+
+        let fixtures = plan.steps.last().unwrap();
+        assert_eq!(fixtures.level, Some(2));
+        // fixtures are made from composites, which need a level 1 factory
+        assert!(products.contains(&Composite));
+        assert_eq!(plan.other_levels, [1]);
+        assert!(plan.raw_resources.contains_key(&Metal));
+        assert_eq!(
+            plan.total_cooldown,
+            plan.steps.iter().map(|step| step.cooldown).sum::<u32>()
+        );
+    }
+}
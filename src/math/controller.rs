@@ -0,0 +1,153 @@
+use screeps::constants::*;
+
+use crate::math::BoostedPart;
+
+/// Provides the total number of control points needed for a newly claimed
+/// controller to reach a given Room Control Level
+///
+/// Returns `None` for levels outside of 1-8.
+pub fn control_points_for_rcl(level: u32) -> Option<u32> {
+    if !(1..=8).contains(&level) {
+        return None;
+    }
+    (1..level).map(controller_levels).sum()
+}
+
+/// Provides the number of ticks until a controller with no further upgrades
+/// downgrades all the way and becomes unclaimed
+///
+/// When a controller loses a level, its downgrade timer is set to half of the
+/// `CONTROLLER_DOWNGRADE` maximum for its new level, so this is the current
+/// timer plus half of the maximum for each lower level. Returns `None` for
+/// levels outside of 1-8.
+pub fn ticks_to_unclaim(level: u32, ticks_to_downgrade: u32) -> Option<u32> {
+    if !(1..=8).contains(&level) {
+        return None;
+    }
+    (1..level as u8)
+        .map(|lower| controller_downgrade(lower).map(|ticks| ticks / 2))
+        .sum::<Option<u32>>()
+        .map(|ticks| ticks + ticks_to_downgrade)
+}
+
+/// Provides the downgrade timer value below which safe mode can't be
+/// activated at a given Room Control Level
+///
+/// Returns `None` for levels outside of 1-8.
+pub fn safe_mode_threshold(level: u32) -> Option<u32> {
+    controller_downgrade(u8::try_from(level).ok()?)
+        .map(|ticks| (ticks / 2).saturating_sub(CONTROLLER_DOWNGRADE_SAFEMODE_THRESHOLD))
+}
+
+/// The game ticks at which a safe mode activation ends and another can next
+/// be activated, as calculated by [`safe_mode_window`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SafeModeWindow {
+    /// The first tick after the safe mode ends
+    pub ends: u32,
+    /// The first tick that safe mode can be activated again
+    pub cooldown_ends: u32,
+}
+
+/// Provides the ticks at which a safe mode activated on the given tick ends,
+/// and at which its cooldown ends.
+pub fn safe_mode_window(activated: u32) -> SafeModeWindow {
+    SafeModeWindow {
+        ends: activated + SAFE_MODE_DURATION,
+        cooldown_ends: activated + SAFE_MODE_COOLDOWN,
+    }
+}
+
+/// Provides the control points added each tick by a body upgrading a
+/// controller, including boosts, assuming it has enough energy for every
+/// `WORK` part.
+///
+/// The game limits upgrading at level 8 to [`CONTROLLER_MAX_UPGRADE_PER_TICK`]
+/// across all creeps, which isn't applied here.
+pub fn body_upgrade_power(body: &[BoostedPart]) -> u32 {
+    body.iter()
+        .filter(|part| part.part == Part::Work)
+        .map(|part| match part.boost.and_then(|boost| boost.boost()) {
+            Some(Boost::UpgradeController(multiplier)) => {
+                UPGRADE_CONTROLLER_POWER as f64 * multiplier as f64
+            }
+            _ => UPGRADE_CONTROLLER_POWER as f64,
+        })
+        .sum::<f64>()
+        .floor() as u32
+}
+
+/// Provides the number of ticks for a controller to reach the next level
+/// given its current progress and the control points added each tick, such as
+/// from [`body_upgrade_power`].
+///
+/// Returns `None` at level 8 or above, for levels below 1, or if the upgrade
+/// rate is 0.
+pub fn ticks_to_next_rcl(level: u32, progress: u32, upgrade_per_tick: u32) -> Option<u32> {
+    let needed = controller_levels(level)?;
+    if upgrade_per_tick == 0 {
+        return None;
+    }
+    Some(needed.saturating_sub(progress).div_ceil(upgrade_per_tick))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rcl_control_points() {
+        assert_eq!(control_points_for_rcl(0), None);
+        assert_eq!(control_points_for_rcl(1), Some(0));
+        assert_eq!(control_points_for_rcl(2), Some(200));
+        assert_eq!(control_points_for_rcl(3), Some(45_200));
+        assert_eq!(control_points_for_rcl(8), Some(16_380_200));
+        assert_eq!(control_points_for_rcl(9), None);
+    }
+
+    #[test]
+    fn downgrade_timeline() {
+        assert_eq!(ticks_to_unclaim(1, 500), Some(500));
+        assert_eq!(ticks_to_unclaim(3, 1_000), Some(1_000 + 5_000 + 10_000));
+        assert_eq!(ticks_to_unclaim(9, 1_000), None);
+
+        assert_eq!(safe_mode_threshold(7), Some(70_000));
+        assert_eq!(safe_mode_threshold(2), Some(0));
+        assert_eq!(safe_mode_threshold(0), None);
+        assert_eq!(safe_mode_threshold(257), None);
+
+        assert_eq!(
+            safe_mode_window(1_000),
+            SafeModeWindow {
+                ends: 21_000,
+                cooldown_ends: 51_000,
+            }
+        );
+    }
+
+    #[test]
+    fn upgrade_timeline() {
+        use ResourceType::*;
+
+        let body = [
+            BoostedPart::boosted(Part::Work, CatalyzedGhodiumAcid),
+            BoostedPart::new(Part::Work),
+            // boosts for other actions have no effect
+            BoostedPart::boosted(Part::Work, UtriumHydride),
+            BoostedPart::new(Part::Carry),
+        ];
+        assert_eq!(body_upgrade_power(&body), 2 + 1 + 1);
+        // fractional boosted power is rounded down over the whole body
+        let body = [
+            BoostedPart::boosted(Part::Work, GhodiumHydride),
+            BoostedPart::boosted(Part::Work, GhodiumHydride),
+            BoostedPart::boosted(Part::Work, GhodiumHydride),
+        ];
+        assert_eq!(body_upgrade_power(&body), 4);
+
+        assert_eq!(ticks_to_next_rcl(2, 40_000, 15), Some(334));
+        assert_eq!(ticks_to_next_rcl(2, 50_000, 15), Some(0));
+        assert_eq!(ticks_to_next_rcl(2, 0, 0), None);
+        assert_eq!(ticks_to_next_rcl(8, 0, 15), None);
+    }
+}
@@ -1,5 +1,7 @@
 use screeps::constants::*;
 
+use crate::math::LevelProgress;
+
 /// Provides the total number of control points needed to achieve a given Global
 /// Control Level
 ///
@@ -13,11 +15,37 @@ pub fn control_points_for_gcl(level: u32) -> f64 {
     ((level - 1) as f64).powf(GCL_POW) * GCL_MULTIPLY as f64
 }
 
+/// Provides the Global Control Level reached with a given total number of
+/// control points, along with the progress toward the next level
+///
+/// This is the inverse of [`control_points_for_gcl`], for working from the
+/// total points spent rather than the game's level and progress values.
+pub fn gcl_for_control_points(points: f64) -> LevelProgress {
+    let points = points.max(0.);
+    let estimate = (points / GCL_MULTIPLY as f64).powf(1. / GCL_POW) as u32;
+    let mut level = estimate.saturating_add(1);
+    // correct the estimate for any floating point error, so the result always
+    // agrees with the forward formula
+    while level > 1 && control_points_for_gcl(level) > points {
+        level -= 1;
+    }
+    while level < u32::MAX && control_points_for_gcl(level + 1) <= points {
+        level += 1;
+    }
+
+    let level_points = control_points_for_gcl(level);
+    LevelProgress {
+        level,
+        progress: points - level_points,
+        progress_total: control_points_for_gcl(level.saturating_add(1)) - level_points,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use assert_approx_eq::assert_approx_eq;
 
-    use super::control_points_for_gcl;
+    use super::*;
 
     #[test]
     fn gcl_formula() {
@@ -65,4 +93,26 @@ mod test {
         // overflows the u32 - this should panic.
         control_points_for_gcl(0);
     }
+
+    #[test]
+    fn gcl_inverse_formula() {
+        let progress = gcl_for_control_points(0.);
+        assert_eq!(progress.level, 1);
+        assert_approx_eq!(progress.progress, 0.);
+        assert_approx_eq!(progress.progress_total, 1000000.);
+
+        let progress = gcl_for_control_points(1500000.);
+        assert_eq!(progress.level, 2);
+        assert_approx_eq!(progress.progress, 500000.);
+        assert_approx_eq!(progress.progress_total, 4278031.643091577);
+        assert_approx_eq!(progress.fraction(), 500000. / 4278031.643091577);
+        assert_eq!(progress.ticks_to_next_level(1000.), Some(3779));
+        assert_eq!(progress.ticks_to_next_level(0.), None);
+
+        for level in 1..200 {
+            let points = control_points_for_gcl(level);
+            assert_eq!(gcl_for_control_points(points).level, level);
+            assert_eq!(gcl_for_control_points(points - 1.).level, level.max(2) - 1);
+        }
+    }
 }
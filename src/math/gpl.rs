@@ -1,5 +1,7 @@
 use screeps::constants::*;
 
+use crate::math::LevelProgress;
+
 /// Provides the total number of processed power needed to achieve a given
 /// Global Power Level
 ///
@@ -13,9 +15,29 @@ pub const fn power_for_gpl(level: u32) -> u128 {
     (level as u128).pow(POWER_LEVEL_POW) * POWER_LEVEL_MULTIPLY as u128
 }
 
+/// Provides the Global Power Level reached with a given total amount of
+/// processed power, along with the progress toward the next level
+///
+/// This is the inverse of [`power_for_gpl`], for working from the total power
+/// processed rather than the game's level and progress values.
+pub fn gpl_for_power(power: u128) -> LevelProgress {
+    // the level is the largest whose squared value, times the multiplier, is
+    // no more than the power processed
+    let level = (power / POWER_LEVEL_MULTIPLY as u128)
+        .isqrt()
+        .min(u32::MAX as u128) as u32;
+
+    let level_power = power_for_gpl(level);
+    LevelProgress {
+        level,
+        progress: (power - level_power) as f64,
+        progress_total: (power_for_gpl(level.saturating_add(1)) - level_power) as f64,
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::power_for_gpl;
+    use super::*;
 
     #[test]
     fn gpl_formula() {
@@ -51,4 +73,26 @@ mod test {
         assert_eq!(power_for_gpl(4_000_000_000), 16_000_000_000_000_000_000_000);
         assert_eq!(power_for_gpl(u32::MAX), 18_446_744_065_119_617_025_000);
     }
+
+    #[test]
+    fn gpl_inverse_formula() {
+        let progress = gpl_for_power(0);
+        assert_eq!(progress.level, 0);
+        assert_eq!(progress.progress, 0.);
+        assert_eq!(progress.progress_total, 1_000.);
+
+        let progress = gpl_for_power(4_500);
+        assert_eq!(progress.level, 2);
+        assert_eq!(progress.progress, 500.);
+        assert_eq!(progress.progress_total, 5_000.);
+        assert_eq!(progress.fraction(), 0.1);
+        assert_eq!(progress.ticks_to_next_level(3.), Some(1_500));
+
+        for level in [1, 2, 10, 1_000, 135_818_792, u32::MAX] {
+            let power = power_for_gpl(level);
+            assert_eq!(gpl_for_power(power).level, level);
+            assert_eq!(gpl_for_power(power - 1).level, level - 1);
+        }
+        assert_eq!(gpl_for_power(u128::MAX).level, u32::MAX);
+    }
 }
@@ -1,12 +1,39 @@
 //! Functions allowing calculation of the resulting values of formulas used by
 //! game mechanics related to constant values.
 
+mod body;
+mod build_schedule;
+mod combat;
+mod commodities;
+mod controller;
 mod gcl;
 mod gpl;
+mod progress;
+mod reactions;
 mod tower;
 
-pub use gcl::control_points_for_gcl;
-pub use gpl::power_for_gpl;
+pub use body::{
+    body_carry_capacity, body_cost, body_fatigue, body_move_power, body_spawn_time,
+    body_ticks_per_tile, generate_body, BoostedPart, MovementGoal, MovementTerrain,
+};
+pub use build_schedule::{
+    build_schedule, partition_by_rcl_limits, PlannedStructures, RclBuildStage,
+};
+pub use combat::{
+    apply_damage, body_attack_power, body_effective_hits, body_heal_power,
+    body_ranged_attack_power, body_ranged_heal_power, body_ranged_mass_attack_power,
+    part_damage_ratio, BodyPartState, DamageResult, BODYPART_HITS,
+};
+pub use commodities::{plan_commodity, CommodityPlan, CommodityStep};
+pub use controller::{
+    body_upgrade_power, control_points_for_rcl, safe_mode_threshold, safe_mode_window,
+    ticks_to_next_rcl, ticks_to_unclaim, SafeModeWindow,
+};
+pub use gcl::{control_points_for_gcl, gcl_for_control_points};
+pub use gpl::{gpl_for_power, power_for_gpl};
+pub use progress::LevelProgress;
+pub use reactions::{plan_reactions, ReactionPlan, ReactionStep};
 pub use tower::{
-    tower_attack_power_at_range, tower_heal_power_at_range, tower_repair_power_at_range,
+    tower_attack_power_at_range, tower_attack_power_map, tower_heal_power_at_range,
+    tower_heal_power_map, tower_repair_power_at_range, tower_repair_power_map,
 };
@@ -0,0 +1,28 @@
+/// A level along with the progress made toward the next one, matching the
+/// `level`, `progress` and `progressTotal` values the game reports for Global
+/// Control Level and Global Power Level.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LevelProgress {
+    pub level: u32,
+    /// The amount gained since reaching the current level
+    pub progress: f64,
+    /// The amount needed to go from the current level to the next
+    pub progress_total: f64,
+}
+
+impl LevelProgress {
+    /// The fraction of the way from the current level to the next, between 0
+    /// and 1.
+    pub fn fraction(&self) -> f64 {
+        self.progress / self.progress_total
+    }
+
+    /// Estimates the number of ticks to reach the next level when gaining the
+    /// given amount each tick; returns `None` if the rate isn't positive.
+    pub fn ticks_to_next_level(&self, rate_per_tick: f64) -> Option<u64> {
+        if rate_per_tick <= 0. {
+            return None;
+        }
+        Some(((self.progress_total - self.progress) / rate_per_tick).ceil() as u64)
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use screeps::constants::*;
+
+/// A single reaction needed as part of a [`ReactionPlan`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReactionStep {
+    /// The compound produced by the reaction
+    pub product: ResourceType,
+    /// The two reagents consumed by the reaction
+    pub reagents: [ResourceType; 2],
+    /// The amount of the product to make, which is also the amount of each
+    /// reagent consumed
+    pub amount: u32,
+    /// The number of times the reaction needs to be run
+    pub reactions: u32,
+    /// The number of ticks a single lab takes to run all the reactions
+    pub ticks: u32,
+}
+
+/// The reactions and base resources needed to produce an amount of a
+/// compound, as calculated by [`plan_reactions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReactionPlan {
+    /// Every reaction needed, ordered so that each reaction's reagents are
+    /// produced by earlier steps
+    pub steps: Vec<ReactionStep>,
+    /// The amount of each resource needed which isn't produced by a reaction,
+    /// such as base minerals
+    pub base_resources: HashMap<ResourceType, u32>,
+    /// The number of ticks a single lab takes to run every step in turn
+    pub total_ticks: u32,
+}
+
+// Adds the compound and everything it's made from to `order`, with reagents
+// always before the compounds made from them
+fn visit(resource: ResourceType, order: &mut Vec<ResourceType>) {
+    if order.contains(&resource) {
+        return;
+    }
+    if let Some(reagents) = resource.reaction_components() {
+        for reagent in reagents {
+            visit(reagent, order);
+        }
+    }
+    order.push(resource);
+}
+
+/// Expands the `REACTIONS` constant into every reaction needed to produce the
+/// given amount of a compound, along with the base resources they consume and
+/// the time they take from the `REACTION_TIME` constant.
+///
+/// Each reaction produces [`LAB_REACTION_AMOUNT`] of its product, so amounts
+/// are rounded up to a multiple of it at each step. Intermediate compounds
+/// needed in several places, like hydroxide, are combined into a single step.
+/// If the target isn't produced by a reaction, the plan only has the target
+/// itself as a base resource.
+pub fn plan_reactions(target: ResourceType, amount: u32) -> ReactionPlan {
+    let mut order = Vec::new();
+    visit(target, &mut order);
+
+    // Consumers always come later in the order than the reagents they need,
+    // so walking it backwards visits each compound after all its consumers
+    let mut demand: HashMap<ResourceType, u32> = HashMap::from([(target, amount)]);
+    let mut steps = Vec::new();
+    let mut base_resources = HashMap::new();
+    for resource in order.iter().rev() {
+        let needed = demand.get(resource).copied().unwrap_or(0);
+        if needed == 0 {
+            continue;
+        }
+        match resource.reaction_components() {
+            Some(reagents) => {
+                let reactions = needed.div_ceil(LAB_REACTION_AMOUNT);
+                let amount = reactions * LAB_REACTION_AMOUNT;
+                for reagent in reagents {
+                    *demand.entry(reagent).or_default() += amount;
+                }
+                steps.push(ReactionStep {
+                    product: *resource,
+                    reagents,
+                    amount,
+                    reactions,
+                    ticks: reactions * resource.reaction_time().unwrap_or(0),
+                });
+            }
+            None => {
+                base_resources.insert(*resource, needed);
+            }
+        }
+    }
+    steps.reverse();
+
+    ReactionPlan {
+        total_ticks: steps.iter().map(|step| step.ticks).sum(),
+        steps,
+        base_resources,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use ResourceType::*;
+
+    #[test]
+    fn plan_single_reaction() {
+        let plan = plan_reactions(Hydroxide, 12);
+        assert_eq!(
+            plan.steps,
+            [ReactionStep {
+                product: Hydroxide,
+                reagents: [Oxygen, Hydrogen],
+                amount: 15,
+                reactions: 3,
+                ticks: 3 * 20,
+            }]
+        );
+        assert_eq!(
+            plan.base_resources,
+            HashMap::from([(Hydrogen, 15), (Oxygen, 15)])
+        );
+        assert_eq!(plan.total_ticks, 60);
+    }
+
+    #[test]
+    fn plan_catalyzed_ghodium_acid() {
+        let plan = plan_reactions(CatalyzedGhodiumAcid, 3000);
+        let products: Vec<_> = plan.steps.iter().map(|step| step.product).collect();
+        assert_eq!(products.len(), 7);
+        assert_eq!(products.last(), Some(&CatalyzedGhodiumAcid));
+        // each step's reagents are made by earlier steps
+        for (idx, step) in plan.steps.iter().enumerate() {
+            for reagent in step.reagents {
+                if reagent.reaction_components().is_some() {
+                    assert!(products[..idx].contains(&reagent));
+                }
+            }
+        }
+
+        assert_eq!(
+            plan.base_resources,
+            HashMap::from([
+                (Catalyst, 3000),
+                (Oxygen, 3000),
+                (Hydrogen, 6000),
+                (Zynthium, 3000),
+                (Keanium, 3000),
+                (Utrium, 3000),
+                (Lemergium, 3000),
+            ])
+        );
+        let expected_ticks = 600 * (80 + 15 + 10 + 5 + 5 + 5 + 20);
+        assert_eq!(plan.total_ticks, expected_ticks);
+    }
+
+    #[test]
+    fn plan_base_mineral() {
+        let plan = plan_reactions(Oxygen, 100);
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.base_resources, HashMap::from([(Oxygen, 100)]));
+        assert_eq!(plan.total_ticks, 0);
+    }
+}
@@ -1,4 +1,6 @@
-use screeps::constants::*;
+use screeps::{constants::*, local::RoomXY};
+
+use crate::large_cost_matrix::LargeCostMatrix;
 
 /// Provides the amount of damage done by tower attacks at a given range, after
 /// accounting for reduction from [`TOWER_FALLOFF`].
@@ -42,6 +44,45 @@ pub fn tower_repair_power_at_range(mut range: u8) -> u32 {
     amount as u32
 }
 
+fn tower_power_map(towers: &[RoomXY], power_at_range: fn(u8) -> u32) -> LargeCostMatrix {
+    let mut power_map = LargeCostMatrix::new();
+    for (xy, cm_val) in power_map.iter_mut() {
+        let total: u32 = towers
+            .iter()
+            .map(|tower| power_at_range(tower.get_range_to(xy)))
+            .sum();
+        *cm_val = total.try_into().unwrap_or(u16::MAX);
+    }
+    power_map
+}
+
+/// Provides a `LargeCostMatrix` with the total damage done by tower attacks at
+/// every position in a room, from towers at each of the given positions.
+///
+/// Totals too large to fit are saturated at u16::MAX, which can't happen with
+/// the game's limit of 6 towers per room.
+pub fn tower_attack_power_map(towers: &[RoomXY]) -> LargeCostMatrix {
+    tower_power_map(towers, tower_attack_power_at_range)
+}
+
+/// Provides a `LargeCostMatrix` with the total damage healed by tower healing
+/// at every position in a room, from towers at each of the given positions.
+///
+/// Totals too large to fit are saturated at u16::MAX, which can't happen with
+/// the game's limit of 6 towers per room.
+pub fn tower_heal_power_map(towers: &[RoomXY]) -> LargeCostMatrix {
+    tower_power_map(towers, tower_heal_power_at_range)
+}
+
+/// Provides a `LargeCostMatrix` with the total damage repaired by towers at
+/// every position in a room, from towers at each of the given positions.
+///
+/// Totals too large to fit are saturated at u16::MAX, which can't happen with
+/// the game's limit of 6 towers per room.
+pub fn tower_repair_power_map(towers: &[RoomXY]) -> LargeCostMatrix {
+    tower_power_map(towers, tower_repair_power_at_range)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -150,4 +191,27 @@ mod test {
         // math should work even at range 0
         assert_eq!(tower_repair_power_at_range(0), 800);
     }
+
+    #[test]
+    fn tower_power_maps() {
+        let towers = [
+            RoomXY::checked_new(10, 10).unwrap(),
+            RoomXY::checked_new(20, 10).unwrap(),
+        ];
+
+        let attack = tower_attack_power_map(&towers);
+        // range 0 and range 10 from the two towers
+        assert_eq!(attack.get(towers[0]), 600 + 450);
+        // range 5 from both towers
+        assert_eq!(attack.get(RoomXY::checked_new(15, 10).unwrap()), 1200);
+        // out past full falloff from both towers
+        assert_eq!(attack.get(RoomXY::checked_new(49, 49).unwrap()), 300);
+
+        let heal = tower_heal_power_map(&towers);
+        assert_eq!(heal.get(RoomXY::checked_new(15, 10).unwrap()), 800);
+        let repair = tower_repair_power_map(&towers);
+        assert_eq!(repair.get(RoomXY::checked_new(15, 10).unwrap()), 1600);
+
+        assert!(tower_attack_power_map(&[]).iter().all(|(_, val)| val == 0));
+    }
 }
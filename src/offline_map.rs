@@ -3,7 +3,7 @@ use std::{collections::HashMap, fs, mem::MaybeUninit};
 use screeps::{
     constants::{Density, ResourceType, ROOM_SIZE},
     game::map::RoomStatus,
-    local::{LocalRoomTerrain, RawObjectId, RoomCoordinate, RoomName},
+    local::{LocalRoomTerrain, RawObjectId, RoomCoordinate, RoomName, RoomXY},
 };
 use serde::{
     de::{Error as _, Unexpected},
@@ -122,6 +122,39 @@ pub enum OfflineObject {
     Unknown,
 }
 
+impl OfflineObject {
+    /// Gets the position of the object within its room, if known.
+    pub fn xy(&self) -> Option<RoomXY> {
+        match self {
+            OfflineObject::ConstructedWall { x, y, .. }
+            | OfflineObject::Controller { x, y, .. }
+            | OfflineObject::Extractor { x, y, .. }
+            | OfflineObject::KeeperLair { x, y, .. }
+            | OfflineObject::Mineral { x, y, .. }
+            | OfflineObject::Portal { x, y, .. }
+            | OfflineObject::Source { x, y, .. }
+            | OfflineObject::Terminal { x, y, .. } => Some(RoomXY { x: *x, y: *y }),
+            OfflineObject::Unknown => None,
+        }
+    }
+
+    /// Whether the object blocks movement onto its position, following the
+    /// game's `OBSTACLE_OBJECT_TYPES` constant.
+    pub fn is_obstacle(&self) -> bool {
+        match self {
+            OfflineObject::ConstructedWall { .. }
+            | OfflineObject::Controller { .. }
+            | OfflineObject::Mineral { .. }
+            | OfflineObject::Source { .. }
+            | OfflineObject::Terminal { .. } => true,
+            OfflineObject::Extractor { .. }
+            | OfflineObject::KeeperLair { .. }
+            | OfflineObject::Portal { .. }
+            | OfflineObject::Unknown => false,
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum OfflinePortalDestination {
@@ -0,0 +1,14 @@
+//! Position checks shared between the algorithms and planning modules.
+
+use screeps::local::{LocalCostMatrix, RoomXY};
+
+// Whether a structure other than a container, road or rampart can be built at
+// the position, which can't be an obstacle, on a room edge or next to an exit
+pub(crate) fn buildable(xy: RoomXY, obstacles: &LocalCostMatrix) -> bool {
+    obstacles.get(xy) != u8::MAX
+        && !xy.is_room_edge()
+        && !xy
+            .neighbors()
+            .iter()
+            .any(|neighbor| neighbor.is_room_edge() && obstacles.get(*neighbor) != u8::MAX)
+}
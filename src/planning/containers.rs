@@ -0,0 +1,212 @@
+use std::cmp::Reverse;
+
+use screeps::local::{LocalCostMatrix, LocalRoomTerrain, RoomXY};
+
+use crate::{
+    algorithms::{
+        floodfill::{get_obstacles_lcm_from_terrain, numerical_floodfill},
+        pathfinding::SearchGoal,
+    },
+    offline_map::{OfflineObject, OfflineRoomData},
+    placement::buildable,
+    room_xy::chebyshev_range_iter,
+};
+
+/// The range from the controller at which containers are placed by
+/// [`plan_containers_from_offline_room`], letting upgraders on every side of
+/// the container stay in range of the controller.
+pub const CONTROLLER_CONTAINER_RANGE: u8 = 2;
+
+/// The chosen container and link positions for a single target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContainerPlacement {
+    /// The position being worked, such as a source or the controller
+    pub target: RoomXY,
+    pub container: RoomXY,
+    /// A position next to the container for a link, if there's room for one
+    pub link: Option<RoomXY>,
+}
+
+/// Chooses a container and link position for each target, treating terrain
+/// walls and the targets themselves as the only obstacles.
+///
+/// See [`plan_containers_from_cost_matrix`] for details.
+pub fn plan_containers_from_terrain(
+    room_terrain: &LocalRoomTerrain,
+    base: RoomXY,
+    targets: &[SearchGoal],
+) -> Vec<Option<ContainerPlacement>> {
+    let mut obstacles = get_obstacles_lcm_from_terrain(room_terrain);
+    for target in targets {
+        obstacles.set(target.pos, u8::MAX);
+    }
+    plan_containers_from_cost_matrix(&obstacles, base, targets)
+}
+
+/// Chooses a container and link position for each of the sources and the
+/// controller of a room from a map dump, treating terrain walls and all
+/// obstacle objects as obstacles.
+///
+/// Containers are placed next to sources, and within
+/// [`CONTROLLER_CONTAINER_RANGE`] of the controller. The results are in the
+/// order of the room's objects, and rooms without sources or a controller have
+/// no results for them.
+///
+/// See [`plan_containers_from_cost_matrix`] for details.
+pub fn plan_containers_from_offline_room(
+    room: &OfflineRoomData,
+    base: RoomXY,
+) -> Vec<Option<ContainerPlacement>> {
+    let mut obstacles = get_obstacles_lcm_from_terrain(&room.terrain);
+    let mut targets = Vec::new();
+    for object in &room.objects {
+        let Some(xy) = object.xy() else {
+            continue;
+        };
+        if object.is_obstacle() {
+            obstacles.set(xy, u8::MAX);
+        }
+        match object {
+            OfflineObject::Source { .. } => targets.push(SearchGoal::new(xy, 1)),
+            OfflineObject::Controller { .. } => {
+                targets.push(SearchGoal::new(xy, CONTROLLER_CONTAINER_RANGE))
+            }
+            _ => (),
+        }
+    }
+    plan_containers_from_cost_matrix(&obstacles, base, &targets)
+}
+
+/// Chooses a container and link position for each target, such as the
+/// room's sources and controller, with the container placed within the
+/// target's range.
+///
+/// The obstacles Cost Matrix should have u8::MAX set on all positions that are
+/// obstacles, and 0 everywhere else. Containers are placed on the position
+/// with the most open neighbours for creeps to work from, breaking ties by the
+/// shortest walking distance to the base; links are placed next to the
+/// container, preferring positions which don't take up space next to the
+/// target, then the shortest walking distance to the base.
+///
+/// Each container and link is treated as an obstacle when placing those of
+/// later targets. Returns `None` for targets where no container position can
+/// be reached from the base.
+pub fn plan_containers_from_cost_matrix(
+    obstacles: &LocalCostMatrix,
+    base: RoomXY,
+    targets: &[SearchGoal],
+) -> Vec<Option<ContainerPlacement>> {
+    let mut obstacles = obstacles.clone();
+    let base_distance = numerical_floodfill(&vec![base], &obstacles, u16::MAX);
+
+    targets
+        .iter()
+        .map(|target| {
+            let open_neighbors = |xy: RoomXY, obstacles: &LocalCostMatrix| {
+                xy.neighbors()
+                    .iter()
+                    .filter(|neighbor| obstacles.get(**neighbor) != u8::MAX)
+                    .count()
+            };
+
+            let container = chebyshev_range_iter(target.pos, target.range)
+                .filter(|xy| {
+                    !xy.is_room_edge()
+                        && obstacles.get(*xy) != u8::MAX
+                        && base_distance.get(*xy) != u16::MAX
+                })
+                .min_by_key(|xy| {
+                    (
+                        Reverse(open_neighbors(*xy, &obstacles)),
+                        base_distance.get(*xy),
+                        *xy,
+                    )
+                })?;
+            obstacles.set(container, u8::MAX);
+
+            let link = chebyshev_range_iter(container, 1)
+                .filter(|xy| buildable(*xy, &obstacles) && base_distance.get(*xy) != u16::MAX)
+                .min_by_key(|xy| {
+                    (
+                        xy.get_range_to(target.pos) <= 1,
+                        base_distance.get(*xy),
+                        *xy,
+                    )
+                });
+            if let Some(link) = link {
+                obstacles.set(link, u8::MAX);
+            }
+
+            Some(ContainerPlacement {
+                target: target.pos,
+                container,
+                link,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    #[test]
+    fn source_in_open_room() {
+        let terrain = LocalRoomTerrain::new_from_bits(Box::new([0; 2500]));
+        let source = make_xy(10, 10);
+        let placements =
+            plan_containers_from_terrain(&terrain, make_xy(25, 10), &[SearchGoal::new(source, 1)]);
+        let placement = placements[0].unwrap();
+
+        // every position next to the source has the same number of open
+        // neighbours, so the one closest to the base wins
+        assert_eq!(placement.target, source);
+        assert_eq!(placement.container.get_range_to(source), 1);
+        assert_eq!(placement.container.x.u8(), 11);
+        let link = placement.link.unwrap();
+        assert_eq!(link.get_range_to(placement.container), 1);
+        assert!(link.get_range_to(source) > 1);
+    }
+
+    #[test]
+    fn prefers_open_positions() {
+        let mut bits = Box::new([0; 2500]);
+        // walls along the side of the source facing the base
+        for y in 8..=12 {
+            bits[y * 50 + 12] = 1;
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        let source = make_xy(10, 10);
+        let controller = make_xy(40, 40);
+        let placements = plan_containers_from_terrain(
+            &terrain,
+            make_xy(25, 10),
+            &[SearchGoal::new(source, 1), SearchGoal::new(controller, 2)],
+        );
+
+        let source_placement = placements[0].unwrap();
+        assert_eq!(source_placement.container.x.u8(), 10);
+        let controller_placement = placements[1].unwrap();
+        assert!(controller_placement.container.get_range_to(controller) <= 2);
+        assert_ne!(controller_placement.link, None);
+
+        // a source walled in completely can't have a container
+        let mut bits = Box::new([0; 2500]);
+        for xy in chebyshev_range_iter(make_xy(30, 30), 2) {
+            if xy.get_range_to(make_xy(30, 30)) == 2 {
+                bits[xy.y.u8() as usize * 50 + xy.x.u8() as usize] = 1;
+            }
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        let placements = plan_containers_from_terrain(
+            &terrain,
+            make_xy(10, 10),
+            &[SearchGoal::new(make_xy(30, 30), 1)],
+        );
+        assert_eq!(placements, [None]);
+    }
+}
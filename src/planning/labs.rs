@@ -0,0 +1,216 @@
+use screeps::local::{LocalCostMatrix, LocalRoomTerrain, RoomXY};
+
+use crate::{
+    algorithms::floodfill::get_obstacles_lcm_from_terrain, placement::buildable,
+    room_xy::chebyshev_range_iter,
+};
+
+/// The range within which a lab can use another lab as a reagent source for
+/// its reactions.
+pub const LAB_REACTION_RANGE: u8 = 2;
+
+/// The number of labs in a cluster generated by
+/// [`generate_lab_clusters_from_cost_matrix`], which is every lab available at
+/// controller level 8.
+pub const LAB_CLUSTER_SIZE: usize = 10;
+
+/// A group of labs where the two input labs are within [`LAB_REACTION_RANGE`]
+/// of every other lab, letting all of the output labs run reactions using
+/// them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LabCluster {
+    pub inputs: [RoomXY; 2],
+    pub outputs: Vec<RoomXY>,
+}
+
+impl LabCluster {
+    /// Checks whether a set of distinct lab positions can be used as a
+    /// cluster, choosing the first two labs which can serve as inputs; returns
+    /// `None` if fewer than two labs can.
+    pub fn from_labs(labs: &[RoomXY]) -> Option<Self> {
+        let inputs = input_labs(labs);
+        let inputs = [*inputs.first()?, *inputs.get(1)?];
+        Some(LabCluster {
+            inputs,
+            outputs: labs
+                .iter()
+                .copied()
+                .filter(|lab| !inputs.contains(lab))
+                .collect(),
+        })
+    }
+
+    /// All of the labs in the cluster, starting with the inputs.
+    pub fn labs(&self) -> Vec<RoomXY> {
+        self.inputs.iter().chain(&self.outputs).copied().collect()
+    }
+}
+
+/// Finds the labs within [`LAB_REACTION_RANGE`] of every other lab, which can
+/// serve as inputs for all of the others.
+///
+/// A set of labs is a valid cluster for running reactions in every other lab
+/// when at least two labs are returned.
+pub fn input_labs(labs: &[RoomXY]) -> Vec<RoomXY> {
+    labs.iter()
+        .copied()
+        .filter(|lab| {
+            labs.iter()
+                .all(|other| lab.get_range_to(*other) <= LAB_REACTION_RANGE)
+        })
+        .collect()
+}
+
+/// Proposes every [`LAB_CLUSTER_SIZE`] lab cluster which fits the room's
+/// terrain, treating terrain walls as the only obstacles.
+///
+/// See [`generate_lab_clusters_from_cost_matrix`] for details.
+pub fn generate_lab_clusters_from_terrain(room_terrain: &LocalRoomTerrain) -> Vec<LabCluster> {
+    generate_lab_clusters_from_cost_matrix(&get_obstacles_lcm_from_terrain(room_terrain))
+}
+
+/// Proposes every [`LAB_CLUSTER_SIZE`] lab cluster which fits around the
+/// obstacles, in the order of their first input lab's position.
+///
+/// The obstacles Cost Matrix should have u8::MAX set on all positions that are
+/// obstacles, and 0 everywhere else. For each pair of input positions next to
+/// each other, the output labs are chosen from the positions in range of both
+/// inputs, preferring those closest to the inputs; positions are skipped where
+/// a lab would leave any lab in the cluster with no open neighbour for creeps
+/// to fill and empty it from. Labs are never placed on room edges or next to
+/// exits.
+pub fn generate_lab_clusters_from_cost_matrix(obstacles: &LocalCostMatrix) -> Vec<LabCluster> {
+    // the second input's offset from the first; other orientations are
+    // covered by the first input being at a different position
+    const INPUT_OFFSETS: [(i8, i8); 4] = [(1, 0), (0, 1), (1, 1), (-1, 1)];
+
+    obstacles
+        .iter()
+        .flat_map(|(first, _)| {
+            INPUT_OFFSETS.iter().filter_map(move |offset| {
+                let second = first.checked_add(*offset)?;
+                generate_lab_cluster(obstacles, [first, second])
+            })
+        })
+        .collect()
+}
+
+fn generate_lab_cluster(obstacles: &LocalCostMatrix, inputs: [RoomXY; 2]) -> Option<LabCluster> {
+    if !inputs.iter().all(|input| buildable(*input, obstacles)) {
+        return None;
+    }
+
+    let mut candidates: Vec<_> = chebyshev_range_iter(inputs[0], LAB_REACTION_RANGE)
+        .filter(|xy| {
+            !inputs.contains(xy)
+                && xy.get_range_to(inputs[1]) <= LAB_REACTION_RANGE
+                && buildable(*xy, obstacles)
+        })
+        .collect();
+    candidates.sort_by_key(|xy| (xy.get_range_to(inputs[0]) + xy.get_range_to(inputs[1]), *xy));
+
+    let mut labs = inputs.to_vec();
+    for candidate in candidates {
+        if labs.len() == LAB_CLUSTER_SIZE {
+            break;
+        }
+        labs.push(candidate);
+        if !labs
+            .iter()
+            .all(|lab| has_open_neighbor(*lab, &labs, obstacles))
+        {
+            labs.pop();
+        }
+    }
+
+    (labs.len() == LAB_CLUSTER_SIZE).then(|| LabCluster {
+        inputs,
+        outputs: labs.split_off(2),
+    })
+}
+
+fn has_open_neighbor(xy: RoomXY, labs: &[RoomXY], obstacles: &LocalCostMatrix) -> bool {
+    xy.neighbors()
+        .iter()
+        .any(|neighbor| obstacles.get(*neighbor) != u8::MAX && !labs.contains(neighbor))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use screeps::constants::StructureType;
+
+    use crate::planning::layout::LayoutStamp;
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    #[test]
+    fn validate_lab_clusters() {
+        let anchor = make_xy(10, 10);
+        let stamp_labs: Vec<_> = LayoutStamp::lab_cluster()
+            .structures()
+            .iter()
+            .filter(|(_, structure)| *structure == StructureType::Lab)
+            .map(|(offset, _)| anchor.checked_add(*offset).unwrap())
+            .collect();
+        let cluster = LabCluster::from_labs(&stamp_labs).unwrap();
+        assert_eq!(cluster.inputs, [make_xy(11, 11), make_xy(12, 12)]);
+        assert_eq!(cluster.outputs.len(), 8);
+
+        // a lab out of range of one of the inputs
+        let labs = [make_xy(10, 10), make_xy(11, 10), make_xy(13, 10)];
+        assert_eq!(input_labs(&labs), [make_xy(11, 10)]);
+        assert_eq!(LabCluster::from_labs(&labs), None);
+    }
+
+    #[test]
+    fn generate_in_open_room() {
+        let terrain = LocalRoomTerrain::new_from_bits(Box::new([0; 2500]));
+        let clusters = generate_lab_clusters_from_terrain(&terrain);
+        assert!(!clusters.is_empty());
+        for cluster in &clusters {
+            let labs = cluster.labs();
+            assert_eq!(labs.len(), LAB_CLUSTER_SIZE);
+            assert_eq!(
+                LabCluster::from_labs(&labs).map(|c| c.labs().len()),
+                Some(10)
+            );
+            assert!(labs.iter().all(
+                |lab| !lab.is_room_edge() && lab.neighbors().iter().any(|n| !labs.contains(n))
+            ));
+        }
+    }
+
+    #[test]
+    fn generate_around_walls() {
+        // a narrow corridor leaves no room for a cluster
+        let mut bits = Box::new([1; 2500]);
+        for x in 5..45 {
+            for y in 20..22 {
+                bits[y * 50 + x] = 0;
+            }
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        assert!(generate_lab_clusters_from_terrain(&terrain).is_empty());
+
+        // widening it gives space for clusters, all within the open area
+        let mut bits = Box::new([1; 2500]);
+        for x in 5..45 {
+            for y in 20..25 {
+                bits[y * 50 + x] = 0;
+            }
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        let clusters = generate_lab_clusters_from_terrain(&terrain);
+        assert!(!clusters.is_empty());
+        for cluster in clusters {
+            assert!(cluster
+                .labs()
+                .iter()
+                .all(|lab| (5..45).contains(&lab.x.u8()) && (20..25).contains(&lab.y.u8())));
+        }
+    }
+}
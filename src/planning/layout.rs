@@ -0,0 +1,691 @@
+use std::collections::HashMap;
+
+use screeps::{
+    constants::{StructureType, Terrain},
+    local::{LocalCostMatrix, LocalRoomTerrain, RoomXY},
+};
+
+use crate::{
+    algorithms::{
+        distance_transform::chebyshev_distance_transform_from_cost_matrix,
+        floodfill::{numerical_floodfill, reachability_floodfill},
+        pathfinding::{search, SearchGoal, SearchOptions},
+        stamp_placement::{find_stamp_anchors_from_distance_transform, Stamp},
+    },
+    math::partition_by_rcl_limits,
+    offline_map::{OfflineObject, OfflineRoomData},
+};
+
+use StructureType::*;
+
+/// A group of structures which are placed in a room together, positioned
+/// relative to an anchor position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutStamp {
+    structures: Vec<((i8, i8), StructureType)>,
+    mask: Stamp,
+}
+
+impl LayoutStamp {
+    /// Creates a stamp from a list of `(x, y)` offsets from its anchor along
+    /// with the structure to build there.
+    ///
+    /// Structures of the same type are built in the order they're listed, as
+    /// the controller level allows.
+    pub fn new(structures: Vec<((i8, i8), StructureType)>) -> Self {
+        let mask = Stamp::new(structures.iter().map(|(offset, _)| *offset).collect());
+        LayoutStamp { structures, mask }
+    }
+
+    /// The central stamp of a base, anchored on the road in its middle: two
+    /// spawns, storage, terminal, factory, power spawn and a link surrounded
+    /// by roads, all reachable from the anchor.
+    pub fn core() -> Self {
+        let mut structures = ring_roads(2);
+        structures.extend([
+            ((-1, -1), Spawn),
+            ((0, -1), Storage),
+            ((1, -1), Spawn),
+            ((-1, 0), Terminal),
+            ((0, 0), Road),
+            ((1, 0), Link),
+            ((-1, 1), Factory),
+            ((0, 1), Road),
+            ((1, 1), PowerSpawn),
+        ]);
+        LayoutStamp::new(structures)
+    }
+
+    /// Five extensions in a plus shape, anchored on the middle extension and
+    /// surrounded by roads.
+    pub fn extension_pod() -> Self {
+        LayoutStamp::new(vec![
+            ((0, 0), Extension),
+            ((0, -1), Extension),
+            ((-1, 0), Extension),
+            ((1, 0), Extension),
+            ((0, 1), Extension),
+            ((-1, -1), Road),
+            ((1, -1), Road),
+            ((-1, 1), Road),
+            ((1, 1), Road),
+            ((0, -2), Road),
+            ((-2, 0), Road),
+            ((2, 0), Road),
+            ((0, 2), Road),
+        ])
+    }
+
+    /// Ten labs in a 4x4 square split by a diagonal road, anchored at the
+    /// top-left corner; the labs at `(1, 1)` and `(2, 2)` are within range 2
+    /// of every other lab, so they're listed first to act as the reagent labs.
+    pub fn lab_cluster() -> Self {
+        LayoutStamp::new(vec![
+            ((1, 1), Lab),
+            ((2, 2), Lab),
+            ((1, 0), Lab),
+            ((2, 0), Lab),
+            ((0, 1), Lab),
+            ((3, 1), Lab),
+            ((0, 2), Lab),
+            ((3, 2), Lab),
+            ((1, 3), Lab),
+            ((2, 3), Lab),
+            ((3, 0), Road),
+            ((2, 1), Road),
+            ((1, 2), Road),
+            ((0, 3), Road),
+        ])
+    }
+
+    /// Six towers in two rows either side of a road, anchored on the middle
+    /// of the road.
+    pub fn tower_block() -> Self {
+        LayoutStamp::new(vec![
+            ((0, -1), Tower),
+            ((0, 1), Tower),
+            ((-1, -1), Tower),
+            ((1, 1), Tower),
+            ((1, -1), Tower),
+            ((-1, 1), Tower),
+            ((-2, 0), Road),
+            ((-1, 0), Road),
+            ((0, 0), Road),
+            ((1, 0), Road),
+            ((2, 0), Road),
+        ])
+    }
+
+    /// The third spawn, nuker and observer in a row beside a road, anchored on
+    /// the middle of the road.
+    pub fn utility_block() -> Self {
+        LayoutStamp::new(vec![
+            ((-1, -1), Spawn),
+            ((0, -1), Nuker),
+            ((1, -1), Observer),
+            ((-1, 0), Road),
+            ((0, 0), Road),
+            ((1, 0), Road),
+        ])
+    }
+
+    /// The structures of the stamp along with their offsets from its anchor.
+    pub fn structures(&self) -> &[((i8, i8), StructureType)] {
+        &self.structures
+    }
+
+    /// The positions covered by the stamp, without regard to what's built
+    /// there.
+    pub fn mask(&self) -> &Stamp {
+        &self.mask
+    }
+}
+
+fn ring_roads(radius: i8) -> Vec<((i8, i8), StructureType)> {
+    (-radius..=radius)
+        .flat_map(|y| (-radius..=radius).map(move |x| (x, y)))
+        .filter(|(x, y)| x.abs() == radius || y.abs() == radius)
+        .map(|offset| (offset, Road))
+        .collect()
+}
+
+/// The standard sequence of stamps for a full base: the core, labs, towers,
+/// utility structures, and enough extension pods for all 60 extensions.
+pub fn default_stamps() -> Vec<LayoutStamp> {
+    let mut stamps = vec![
+        LayoutStamp::core(),
+        LayoutStamp::lab_cluster(),
+        LayoutStamp::tower_block(),
+        LayoutStamp::utility_block(),
+    ];
+    stamps.extend(vec![LayoutStamp::extension_pod(); 12]);
+    stamps
+}
+
+/// The natural features of a room which a layout needs to keep clear of and
+/// accessible.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RoomFeatures {
+    pub controller: Option<RoomXY>,
+    pub sources: Vec<RoomXY>,
+    pub mineral: Option<RoomXY>,
+    /// Any other positions which can't be built on or walked through, such as
+    /// constructed walls or keeper lairs
+    pub obstacles: Vec<RoomXY>,
+}
+
+impl RoomFeatures {
+    /// Collects the features of a room from a map dump.
+    pub fn from_offline_room(room: &OfflineRoomData) -> Self {
+        let mut features = RoomFeatures::default();
+        for object in &room.objects {
+            let Some(xy) = object.xy() else {
+                continue;
+            };
+            match object {
+                OfflineObject::Controller { .. } => features.controller = Some(xy),
+                OfflineObject::Source { .. } => features.sources.push(xy),
+                OfflineObject::Mineral { .. } => features.mineral = Some(xy),
+                _ => features.obstacles.push(xy),
+            }
+        }
+        features
+    }
+
+    fn key_positions(&self) -> impl Iterator<Item = RoomXY> + '_ {
+        self.controller
+            .iter()
+            .chain(self.sources.iter())
+            .chain(self.mineral.iter())
+            .copied()
+    }
+}
+
+/// A planned layout of structures in a room, as created by [`plan_layout`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoomLayout {
+    /// The anchor position of the first stamp, which all roads connect to
+    pub hub: RoomXY,
+    /// Every planned structure in the order they should be built, where the
+    /// controller level allows
+    pub structures: Vec<(RoomXY, StructureType)>,
+    /// The indices of any stamps which couldn't be placed
+    pub unplaced_stamps: Vec<usize>,
+}
+
+impl RoomLayout {
+    /// Gets all planned structures, regardless of controller level.
+    pub fn all_structures(&self) -> HashMap<RoomXY, StructureType> {
+        self.structures.iter().copied().collect()
+    }
+
+    /// Gets the planned structures which can be built at the given controller
+    /// level, taking the earliest planned structures of each type up to the
+    /// level's limit.
+    pub fn structures_at_rcl(&self, rcl: u32) -> HashMap<RoomXY, StructureType> {
+        let (available, _) = partition_by_rcl_limits(&self.structures, rcl);
+        available.into_iter().collect()
+    }
+
+    /// Whether every planned structure is on a position where it can be
+    /// built: not on a terrain wall or the edge of the room, and with no
+    /// position planned for more than one structure.
+    pub fn fits_terrain(&self, room_terrain: &LocalRoomTerrain) -> bool {
+        let mut seen = LocalCostMatrix::new();
+        self.structures.iter().all(|(xy, _)| {
+            let first = seen.get(*xy) == 0;
+            seen.set(*xy, 1);
+            first && !xy.is_room_edge() && room_terrain.get_xy(*xy) != Terrain::Wall
+        })
+    }
+}
+
+struct Planner<'a> {
+    room_terrain: &'a LocalRoomTerrain,
+    features: &'a RoomFeatures,
+    // Positions where no stamp may be placed, with u8::MAX
+    reserved: LocalCostMatrix,
+    // Chebyshev distance from the nearest reserved position
+    distance_transform: LocalCostMatrix,
+    planned: HashMap<RoomXY, StructureType>,
+    structures: Vec<(RoomXY, StructureType)>,
+}
+
+impl<'a> Planner<'a> {
+    fn new(room_terrain: &'a LocalRoomTerrain, features: &'a RoomFeatures) -> Self {
+        let mut reserved = LocalCostMatrix::new();
+        for (xy, val) in reserved.iter_mut() {
+            // Most structures can't be built next to exits, so keep stamps off
+            // the two outermost rows of the room entirely
+            let near_edge = [xy.x.u8(), xy.y.u8()]
+                .iter()
+                .any(|coord| *coord <= 1 || *coord >= 48);
+            if near_edge || room_terrain.get_xy(xy) == Terrain::Wall {
+                *val = u8::MAX;
+            }
+        }
+        // Keep the positions around natural features free for creeps working
+        // on them
+        for xy in features.key_positions() {
+            reserved.set(xy, u8::MAX);
+            for neighbor in xy.neighbors() {
+                reserved.set(neighbor, u8::MAX);
+            }
+        }
+        for xy in &features.obstacles {
+            reserved.set(*xy, u8::MAX);
+        }
+
+        let mut initial_cm = LocalCostMatrix::new();
+        for (xy, val) in initial_cm.iter_mut() {
+            *val = match reserved.get(xy) {
+                u8::MAX => 0,
+                _ => u8::MAX,
+            };
+        }
+
+        Planner {
+            room_terrain,
+            features,
+            reserved,
+            distance_transform: chebyshev_distance_transform_from_cost_matrix(initial_cm),
+            planned: HashMap::new(),
+            structures: Vec::new(),
+        }
+    }
+
+    // Positions creeps can't walk through, with u8::MAX
+    fn obstacles(&self) -> LocalCostMatrix {
+        let mut obstacles = LocalCostMatrix::new();
+        for (xy, val) in obstacles.iter_mut() {
+            if self.room_terrain.get_xy(xy) == Terrain::Wall {
+                *val = u8::MAX;
+            }
+        }
+        for xy in self
+            .features
+            .key_positions()
+            .chain(self.features.obstacles.iter().copied())
+        {
+            obstacles.set(xy, u8::MAX);
+        }
+        for (xy, structure_type) in &self.planned {
+            if *structure_type != Road {
+                obstacles.set(*xy, u8::MAX);
+            }
+        }
+        obstacles
+    }
+
+    fn placements(
+        &self,
+        stamp: &LayoutStamp,
+        anchor: RoomXY,
+    ) -> Option<Vec<(RoomXY, StructureType)>> {
+        let positions = stamp.mask().positions_at(anchor)?;
+        // Past the stamp's radius from any reserved position, only the
+        // planned structures need checking
+        let clear = self.distance_transform.get(anchor) > stamp.mask().radius();
+        positions
+            .into_iter()
+            .zip(
+                stamp
+                    .structures()
+                    .iter()
+                    .map(|(_, structure_type)| *structure_type),
+            )
+            .map(|(xy, structure_type)| {
+                if !clear && self.reserved.get(xy) == u8::MAX {
+                    return None;
+                }
+                match self.planned.get(&xy) {
+                    None => Some((xy, structure_type)),
+                    Some(Road) if structure_type == Road => Some((xy, structure_type)),
+                    Some(_) => None,
+                }
+            })
+            .collect()
+    }
+
+    // Whether every road and natural feature can be reached from the origins,
+    // and every structure can be reached by a creep next to it
+    fn accessible(&self, origins: &Vec<RoomXY>) -> bool {
+        let reachable = reachability_floodfill(origins, &self.obstacles());
+        let has_reachable_neighbor = |xy: &RoomXY| {
+            xy.neighbors()
+                .iter()
+                .any(|neighbor| reachable.get(*neighbor) == 1)
+        };
+
+        self.planned
+            .iter()
+            .all(|(xy, structure_type)| match structure_type {
+                Road => reachable.get(*xy) == 1,
+                _ => has_reachable_neighbor(xy),
+            })
+            && self
+                .features
+                .key_positions()
+                .all(|xy| has_reachable_neighbor(&xy))
+    }
+
+    fn try_place(
+        &mut self,
+        placements: &[(RoomXY, StructureType)],
+        origins: Option<&Vec<RoomXY>>,
+        hub: RoomXY,
+    ) -> bool {
+        let new: Vec<_> = placements
+            .iter()
+            .filter(|(xy, _)| !self.planned.contains_key(xy))
+            .copied()
+            .collect();
+        self.planned.extend(new.iter().copied());
+
+        let core_origins;
+        let origins = match origins {
+            Some(origins) => origins,
+            None => {
+                core_origins = road_positions(placements, hub);
+                &core_origins
+            }
+        };
+
+        let connecting_roads = self.connecting_roads(placements, hub);
+        let placed = match connecting_roads {
+            Some(roads) => {
+                self.planned.extend(roads.iter().map(|xy| (*xy, Road)));
+                if self.accessible(origins) {
+                    self.structures.extend(new.iter().copied());
+                    self.structures
+                        .extend(roads.into_iter().map(|xy| (xy, Road)));
+                    true
+                } else {
+                    for xy in roads {
+                        self.planned.remove(&xy);
+                    }
+                    false
+                }
+            }
+            None => false,
+        };
+
+        if !placed {
+            for (xy, _) in new {
+                self.planned.remove(&xy);
+            }
+        }
+        placed
+    }
+
+    // Finds the new roads needed to join the stamp's roads to the hub,
+    // preferring to follow roads that are already planned
+    fn connecting_roads(
+        &self,
+        placements: &[(RoomXY, StructureType)],
+        hub: RoomXY,
+    ) -> Option<Vec<RoomXY>> {
+        let Some(start) = placements
+            .iter()
+            .find(|(_, structure_type)| *structure_type == Road)
+            .map(|(xy, _)| *xy)
+        else {
+            return Some(Vec::new());
+        };
+
+        let mut costs = LocalCostMatrix::new();
+        for (xy, val) in costs.iter_mut() {
+            *val = match self.room_terrain.get_xy(xy) {
+                _ if xy.is_room_edge() => u8::MAX,
+                Terrain::Wall => u8::MAX,
+                Terrain::Swamp => 4,
+                Terrain::Plain => 2,
+            };
+        }
+        for xy in self
+            .features
+            .key_positions()
+            .chain(self.features.obstacles.iter().copied())
+        {
+            costs.set(xy, u8::MAX);
+        }
+        for (xy, structure_type) in &self.planned {
+            costs.set(*xy, if *structure_type == Road { 1 } else { u8::MAX });
+        }
+
+        let results = search(
+            start,
+            &[SearchGoal::new(hub, 1)],
+            &mut costs,
+            SearchOptions {
+                max_ops: 10000,
+                ..Default::default()
+            },
+        );
+        if results.incomplete {
+            return None;
+        }
+        Some(
+            results
+                .path
+                .into_iter()
+                .filter(|xy| !self.planned.contains_key(xy))
+                .collect(),
+        )
+    }
+}
+
+fn road_positions(placements: &[(RoomXY, StructureType)], fallback: RoomXY) -> Vec<RoomXY> {
+    let roads: Vec<_> = placements
+        .iter()
+        .filter(|(_, structure_type)| *structure_type == Road)
+        .map(|(xy, _)| *xy)
+        .collect();
+    if roads.is_empty() {
+        vec![fallback]
+    } else {
+        roads
+    }
+}
+
+/// Plans a layout of structures for a room by placing each of the stamps in
+/// turn, such as those from [`default_stamps`].
+///
+/// The first stamp is placed as close as possible to the controller, sources
+/// and mineral, and its anchor becomes the layout's hub. Each later stamp is
+/// placed at the position with the shortest walking distance from the first
+/// stamp's roads, with new roads laid to connect it to the hub. Stamps may
+/// share roads, but otherwise never overlap each other, terrain walls, the
+/// edges of the room, or the positions around the room's features.
+///
+/// Every placement is checked so that all roads stay connected to the hub,
+/// every structure and feature stays reachable, and stamps which can't be
+/// placed anywhere are skipped and recorded in
+/// [`RoomLayout::unplaced_stamps`]. An extractor is added on the mineral, if
+/// any. Returns `None` if the first stamp can't be placed.
+pub fn plan_layout(
+    room_terrain: &LocalRoomTerrain,
+    features: &RoomFeatures,
+    stamps: &[LayoutStamp],
+) -> Option<RoomLayout> {
+    let (core, rest) = stamps.split_first()?;
+    let mut planner = Planner::new(room_terrain, features);
+
+    let targets: Vec<_> = features.key_positions().collect();
+    let hub = find_stamp_anchors_from_distance_transform(
+        &planner.distance_transform,
+        core.mask(),
+        &targets,
+    )
+    .into_iter()
+    .find_map(|candidate| {
+        let placements = planner.placements(core, candidate.anchor)?;
+        planner
+            .try_place(&placements, None, candidate.anchor)
+            .then_some(candidate.anchor)
+    })?;
+
+    let origins = road_positions(&planner.structures, hub);
+    let walking_distance = numerical_floodfill(&origins, &planner.obstacles(), u16::MAX);
+    let mut anchors: Vec<_> = planner
+        .distance_transform
+        .iter()
+        .map(|(xy, _)| xy)
+        .filter(|xy| walking_distance.get(*xy) != u16::MAX)
+        .collect();
+    anchors.sort_by_key(|xy| (walking_distance.get(*xy), *xy));
+
+    let mut unplaced_stamps = Vec::new();
+    for (idx, stamp) in rest.iter().enumerate() {
+        let placed = anchors.iter().any(|anchor| {
+            planner
+                .placements(stamp, *anchor)
+                .is_some_and(|placements| planner.try_place(&placements, Some(&origins), hub))
+        });
+        if !placed {
+            unplaced_stamps.push(idx + 1);
+        }
+    }
+
+    if let Some(mineral) = features.mineral {
+        planner.structures.push((mineral, Extractor));
+    }
+
+    Some(RoomLayout {
+        hub,
+        structures: planner.structures,
+        unplaced_stamps,
+    })
+}
+
+/// Plans a layout of structures for a room from a map dump, using the
+/// positions of its objects as its features.
+///
+/// See [`plan_layout`] for details.
+pub fn plan_layout_from_offline_room(
+    room: &OfflineRoomData,
+    stamps: &[LayoutStamp],
+) -> Option<RoomLayout> {
+    plan_layout(
+        &room.terrain,
+        &RoomFeatures::from_offline_room(room),
+        stamps,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    fn open_room() -> LocalRoomTerrain {
+        LocalRoomTerrain::new_from_bits(Box::new([0; 2500]))
+    }
+
+    fn count(structures: &HashMap<RoomXY, StructureType>, structure_type: StructureType) -> usize {
+        structures
+            .values()
+            .filter(|placed| **placed == structure_type)
+            .count()
+    }
+
+    #[test]
+    fn stamp_roads_reach_structures() {
+        for stamp in default_stamps() {
+            let roads: Vec<_> = stamp
+                .structures()
+                .iter()
+                .filter(|(_, structure_type)| *structure_type == Road)
+                .map(|(offset, _)| *offset)
+                .collect();
+            for ((x, y), _) in stamp.structures() {
+                assert!(roads
+                    .iter()
+                    .any(|(road_x, road_y)| (x - road_x).abs() <= 1 && (y - road_y).abs() <= 1));
+            }
+        }
+    }
+
+    #[test]
+    fn plan_full_base_in_open_room() {
+        let terrain = open_room();
+        let features = RoomFeatures {
+            controller: Some(make_xy(25, 10)),
+            sources: vec![make_xy(10, 30), make_xy(40, 35)],
+            mineral: Some(make_xy(30, 44)),
+            obstacles: vec![],
+        };
+        let layout = plan_layout(&terrain, &features, &default_stamps()).unwrap();
+        assert!(layout.unplaced_stamps.is_empty());
+        assert!(layout.fits_terrain(&terrain));
+
+        let all = layout.all_structures();
+        assert_eq!(all.get(&layout.hub), Some(&Road));
+        assert_eq!(all.get(&make_xy(30, 44)), Some(&Extractor));
+        assert_eq!(count(&all, Extension), 60);
+        assert_eq!(count(&all, Spawn), 3);
+        assert_eq!(count(&all, Lab), 10);
+        assert_eq!(count(&all, Tower), 6);
+
+        // nothing is planned around the room's features
+        for xy in features.key_positions() {
+            assert!(xy
+                .neighbors()
+                .iter()
+                .all(|neighbor| !all.contains_key(neighbor)));
+        }
+
+        // every road is connected to the hub by roads
+        let mut off_road = LocalCostMatrix::new();
+        for (xy, val) in off_road.iter_mut() {
+            if all.get(&xy) != Some(&Road) {
+                *val = u8::MAX;
+            }
+        }
+        let connected = reachability_floodfill(&vec![layout.hub], &off_road);
+        for (xy, structure_type) in &all {
+            if *structure_type == Road {
+                assert_eq!(connected.get(*xy), 1);
+            }
+        }
+
+        let rcl1 = layout.structures_at_rcl(1);
+        assert_eq!(count(&rcl1, Spawn), 1);
+        assert_eq!(count(&rcl1, Extension), 0);
+        let rcl5 = layout.structures_at_rcl(5);
+        assert_eq!(count(&rcl5, Extension), 30);
+        assert_eq!(count(&rcl5, Tower), 2);
+        assert_eq!(layout.structures_at_rcl(8), all);
+    }
+
+    #[test]
+    fn plan_around_walls() {
+        let mut bits = Box::new([0; 2500]);
+        // fill the whole room except for a 9x9 area
+        for y in 0..50 {
+            for x in 0..50 {
+                if !(20..29).contains(&x) || !(20..29).contains(&y) {
+                    bits[y * 50 + x] = 1;
+                }
+            }
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        let layout = plan_layout(&terrain, &RoomFeatures::default(), &default_stamps()).unwrap();
+        assert!(layout.fits_terrain(&terrain));
+        assert!(layout.all_structures().contains_key(&layout.hub));
+        assert!(!layout.unplaced_stamps.is_empty());
+
+        // nothing fits in a room that's too small for the core
+        let mut bits = Box::new([1; 2500]);
+        for y in 20..24 {
+            for x in 20..24 {
+                bits[y * 50 + x] = 0;
+            }
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        assert!(plan_layout(&terrain, &RoomFeatures::default(), &default_stamps()).is_none());
+    }
+}
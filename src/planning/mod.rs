@@ -0,0 +1,7 @@
+//! Offline planning of room layouts, built on the crate's algorithms.
+
+pub mod containers;
+pub mod labs;
+pub mod layout;
+pub mod roads;
+pub mod towers;
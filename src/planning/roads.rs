@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use screeps::{
+    constants::Terrain,
+    local::{LocalRoomTerrain, RoomXY},
+};
+
+use crate::{
+    algorithms::pathfinding::{search, SearchGoal, SearchOptions},
+    large_cost_matrix::{LargeCostMatrix, ROOM_AREA},
+};
+
+/// Options controlling the costs used when planning roads.
+///
+/// All costs must be below u8::MAX, as they're used with the native
+/// [`search`] function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoadPlanOptions {
+    /// Cost of laying a new road on plain terrain
+    pub plain_cost: u16,
+    /// Cost of laying a new road on swamp terrain
+    pub swamp_cost: u16,
+    /// Cost of following a road that's already planned; the lower this is
+    /// compared to the terrain costs, the more roads will be shared between
+    /// targets
+    pub road_cost: u16,
+}
+
+impl Default for RoadPlanOptions {
+    fn default() -> Self {
+        RoadPlanOptions {
+            plain_cost: 2,
+            swamp_cost: 4,
+            road_cost: 1,
+        }
+    }
+}
+
+/// The results of planning a road network, as calculated by
+/// [`plan_roads_from_cost_matrix`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoadPlan {
+    /// The positions of every new road, not including any existing roads
+    pub roads: Vec<RoomXY>,
+    /// The number of road positions between the hub and each target, in the
+    /// same order as the targets, or `None` for targets which can't be reached
+    pub path_lengths: Vec<Option<u32>>,
+}
+
+/// Plans roads connecting the hub to within range of each target, treating
+/// terrain walls and the edges of the room as the only obstacles.
+///
+/// See [`plan_roads_from_cost_matrix`] for details.
+pub fn plan_roads_from_terrain(
+    room_terrain: &LocalRoomTerrain,
+    hub: RoomXY,
+    targets: &[SearchGoal],
+    existing_roads: &[RoomXY],
+    options: RoadPlanOptions,
+) -> RoadPlan {
+    let mut costs = LargeCostMatrix::new();
+    for (xy, cm_val) in costs.iter_mut() {
+        *cm_val = match room_terrain.get_xy(xy) {
+            _ if xy.is_room_edge() => u16::MAX,
+            Terrain::Wall => u16::MAX,
+            Terrain::Swamp => options.swamp_cost,
+            Terrain::Plain => options.plain_cost,
+        };
+    }
+    plan_roads_from_cost_matrix(&costs, hub, targets, existing_roads, options.road_cost)
+}
+
+/// Plans roads connecting the hub to within range of each target, such as the
+/// spawn, sources, controller, mineral and exits of a room.
+///
+/// The costs matrix holds the cost of laying a road on each position, with
+/// u16::MAX set on all positions where roads can't be built, such as walls,
+/// the edges of the room and planned structures; all other values must be
+/// below u8::MAX. The existing roads, and each new road once it has been
+/// planned, have their cost replaced with `road_cost`, so that the paths to
+/// later targets are drawn towards sharing them.
+///
+/// Targets are connected one at a time in the order provided, so listing the
+/// furthest or most important targets first gives the others more roads to
+/// share. Exits on the edges of the room should be given a range of at least 1,
+/// as roads can't be built on room edges.
+pub fn plan_roads_from_cost_matrix(
+    costs: &LargeCostMatrix,
+    hub: RoomXY,
+    targets: &[SearchGoal],
+    existing_roads: &[RoomXY],
+    road_cost: u16,
+) -> RoadPlan {
+    let mut costs = costs.clone();
+    // track roads separately from their costs, as terrain costs may be the
+    // same as `road_cost`
+    let mut planned: HashSet<RoomXY> = existing_roads.iter().copied().collect();
+    for xy in existing_roads {
+        costs.set(*xy, road_cost);
+    }
+
+    let options = SearchOptions {
+        max_ops: ROOM_AREA as u32,
+        ..Default::default()
+    };
+
+    let mut roads = Vec::new();
+    let path_lengths = targets
+        .iter()
+        .map(|target| {
+            let results = search(hub, &[*target], &mut costs, options);
+            if results.incomplete {
+                return None;
+            }
+            for xy in &results.path {
+                if planned.insert(*xy) {
+                    costs.set(*xy, road_cost);
+                    roads.push(*xy);
+                }
+            }
+            Some(results.path.len() as u32)
+        })
+        .collect();
+
+    RoadPlan {
+        roads,
+        path_lengths,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    #[test]
+    fn roads_shared_between_targets() {
+        let terrain = LocalRoomTerrain::new_from_bits(Box::new([0; ROOM_AREA]));
+        let hub = make_xy(25, 25);
+        let targets = [
+            SearchGoal::new(make_xy(25, 5), 1),
+            SearchGoal::new(make_xy(27, 5), 1),
+        ];
+
+        let plan =
+            plan_roads_from_terrain(&terrain, hub, &targets, &[], RoadPlanOptions::default());
+        // the second path is slightly longer, as it follows the first one
+        // rather than laying a parallel road
+        assert_eq!(plan.path_lengths, [Some(19), Some(21)]);
+        // only two new roads are needed to branch off to the second target
+        assert_eq!(plan.roads.len(), 21);
+        assert!(!plan.roads.contains(&hub));
+        assert!(targets
+            .iter()
+            .all(|target| plan.roads.iter().any(|xy| target.is_satisfied_by(*xy))));
+
+        // existing roads which already reach a target don't need any more
+        let existing = plan.roads.clone();
+        let replanned = plan_roads_from_terrain(
+            &terrain,
+            hub,
+            &targets,
+            &existing,
+            RoadPlanOptions::default(),
+        );
+        assert!(replanned.roads.is_empty());
+        assert_eq!(replanned.path_lengths, plan.path_lengths);
+    }
+
+    #[test]
+    fn terrain_cost_same_as_road_cost() {
+        let terrain = LocalRoomTerrain::new_from_bits(Box::new([0; ROOM_AREA]));
+        let hub = make_xy(25, 25);
+        let targets = [SearchGoal::new(make_xy(25, 5), 1)];
+        let options = RoadPlanOptions {
+            plain_cost: 1,
+            swamp_cost: 5,
+            road_cost: 1,
+        };
+
+        let plan = plan_roads_from_terrain(&terrain, hub, &targets, &[], options);
+        assert_eq!(plan.path_lengths, [Some(19)]);
+        assert_eq!(plan.roads.len(), 19);
+
+        let replanned = plan_roads_from_terrain(&terrain, hub, &targets, &plan.roads, options);
+        assert!(replanned.roads.is_empty());
+    }
+
+    #[test]
+    fn unreachable_target() {
+        let mut bits = Box::new([0; ROOM_AREA]);
+        // a wall across the room at y = 10
+        for x in 0..50 {
+            bits[10 * 50 + x] = 1;
+        }
+        let terrain = LocalRoomTerrain::new_from_bits(bits);
+        let targets = [
+            SearchGoal::new(make_xy(25, 5), 1),
+            SearchGoal::new(make_xy(25, 40), 1),
+        ];
+        let plan = plan_roads_from_terrain(
+            &terrain,
+            make_xy(25, 25),
+            &targets,
+            &[],
+            RoadPlanOptions::default(),
+        );
+        assert_eq!(plan.path_lengths, [None, Some(14)]);
+        assert_eq!(plan.roads.len(), 14);
+    }
+}
@@ -0,0 +1,181 @@
+use std::cmp::Reverse;
+
+use screeps::local::RoomXY;
+
+use crate::math::tower_attack_power_at_range;
+
+/// A set of tower positions chosen by [`optimize_tower_placement`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TowerPlacement {
+    /// The chosen tower positions
+    pub towers: Vec<RoomXY>,
+    /// The lowest total tower damage on any of the targets
+    pub min_damage: u32,
+    /// The sum of the total tower damage on every target
+    pub total_damage: u32,
+}
+
+// Damage from each candidate to each target, indexed by candidate then target
+struct DamageTable {
+    damage: Vec<Vec<u32>>,
+}
+
+impl DamageTable {
+    // Scores a set of candidates by the lowest damage on any target, then by
+    // the total damage on all targets
+    fn score(&self, chosen: &[usize], targets: usize) -> (u32, u32) {
+        let per_target = (0..targets).map(|target| {
+            chosen
+                .iter()
+                .map(|candidate| self.damage[*candidate][target])
+                .sum::<u32>()
+        });
+        per_target.fold(
+            (if targets == 0 { 0 } else { u32::MAX }, 0),
+            |(min, total), damage| (min.min(damage), total + damage),
+        )
+    }
+}
+
+/// Chooses up to `count` tower positions from the candidates to maximise the
+/// lowest total tower damage on any of the targets, such as the ramparts or
+/// exits of a room, breaking ties by the total damage on all targets.
+///
+/// Towers are first chosen greedily, then improved by swapping chosen towers
+/// for other candidates until no single swap gives a better result; this
+/// finds the best placement in most practical cases, but isn't guaranteed to.
+/// If there are no more candidates than `count`, all of them are chosen.
+pub fn optimize_tower_placement(
+    candidates: &[RoomXY],
+    targets: &[RoomXY],
+    count: usize,
+) -> TowerPlacement {
+    let table = DamageTable {
+        damage: candidates
+            .iter()
+            .map(|candidate| {
+                targets
+                    .iter()
+                    .map(|target| tower_attack_power_at_range(candidate.get_range_to(*target)))
+                    .collect()
+            })
+            .collect(),
+    };
+    let score = |chosen: &[usize]| table.score(chosen, targets.len());
+
+    let mut chosen: Vec<usize> = Vec::new();
+    while chosen.len() < count.min(candidates.len()) {
+        let best = (0..candidates.len())
+            .filter(|candidate| !chosen.contains(candidate))
+            .max_by_key(|candidate| {
+                let mut with_candidate = chosen.clone();
+                with_candidate.push(*candidate);
+                // prefer earlier candidates when tied
+                (score(&with_candidate), Reverse(*candidate))
+            });
+        match best {
+            Some(best) => chosen.push(best),
+            None => break,
+        }
+    }
+
+    let mut current_score = score(&chosen);
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for slot in 0..chosen.len() {
+            for candidate in 0..candidates.len() {
+                if chosen.contains(&candidate) {
+                    continue;
+                }
+                let previous = chosen[slot];
+                chosen[slot] = candidate;
+                let new_score = score(&chosen);
+                if new_score > current_score {
+                    current_score = new_score;
+                    improved = true;
+                } else {
+                    chosen[slot] = previous;
+                }
+            }
+        }
+    }
+
+    TowerPlacement {
+        towers: chosen.iter().map(|idx| candidates[*idx]).collect(),
+        min_damage: current_score.0,
+        total_damage: current_score.1,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::checked_new(x, y).unwrap()
+    }
+
+    #[test]
+    fn towers_near_targets() {
+        let candidates: Vec<_> = (5..45).map(|x| make_xy(x, 25)).collect();
+        let targets = [make_xy(5, 10), make_xy(5, 40)];
+        let placement = optimize_tower_placement(&candidates, &targets, 3);
+        assert_eq!(placement.towers.len(), 3);
+        // every tower is as close as possible to both targets
+        assert!(placement.towers.iter().all(|xy| xy.x.u8() <= 10));
+        assert_eq!(placement.min_damage, 3 * tower_attack_power_at_range(15));
+    }
+
+    #[test]
+    fn matches_exhaustive_search() {
+        let candidates = [
+            make_xy(10, 10),
+            make_xy(20, 12),
+            make_xy(30, 30),
+            make_xy(25, 40),
+            make_xy(12, 35),
+            make_xy(40, 15),
+            make_xy(24, 24),
+        ];
+        let targets = [
+            make_xy(2, 2),
+            make_xy(47, 2),
+            make_xy(2, 47),
+            make_xy(47, 47),
+            make_xy(25, 2),
+        ];
+        let placement = optimize_tower_placement(&candidates, &targets, 2);
+
+        let damage = |towers: &[RoomXY]| {
+            targets
+                .iter()
+                .map(|target| {
+                    towers
+                        .iter()
+                        .map(|tower| tower_attack_power_at_range(tower.get_range_to(*target)))
+                        .sum::<u32>()
+                })
+                .min()
+                .unwrap()
+        };
+        let best = (0..candidates.len())
+            .flat_map(|a| (a + 1..candidates.len()).map(move |b| (a, b)))
+            .map(|(a, b)| damage(&[candidates[a], candidates[b]]))
+            .max()
+            .unwrap();
+        assert_eq!(placement.min_damage, best);
+        assert_eq!(damage(&placement.towers), best);
+    }
+
+    #[test]
+    fn fewer_candidates_than_towers() {
+        let candidates = [make_xy(10, 10)];
+        let placement = optimize_tower_placement(&candidates, &[make_xy(10, 15)], 6);
+        assert_eq!(placement.towers, candidates);
+        assert_eq!(placement.min_damage, 600);
+
+        let placement = optimize_tower_placement(&candidates, &[], 6);
+        assert_eq!(placement.min_damage, 0);
+    }
+}